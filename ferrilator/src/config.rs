@@ -0,0 +1,181 @@
+use std::ffi::CString;
+use std::ffi::c_char;
+
+/// Settings applied when a model is created, passed to the generated
+/// `with_config` constructor.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Config {
+    threads: u32,
+    args: Option<Vec<String>>,
+    seed: Option<u64>,
+}
+
+impl Config {
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Evaluate the model with `threads` threads. Only models built with the
+    /// `threads` option are multithreaded, and they always use at least the
+    /// number given there. Verilator 4 fixes the count when the model is
+    /// built and ignores this.
+    pub fn with_threads(mut self, threads: u32) -> Config {
+        self.threads = threads;
+        self
+    }
+
+    /// The requested number of threads, 0 if not set.
+    pub fn threads(&self) -> u32 {
+        self.threads
+    }
+
+    /// Pass command line arguments to the model, such as plusargs read by
+    /// `$test$plusargs` and `$value$plusargs`.
+    pub fn with_args(mut self, args: &[&str]) -> Config {
+        self.args = Some(args.iter().map(|arg| arg.to_string()).collect());
+        self
+    }
+
+    /// The arguments given to `with_args`, or if there were none, those in
+    /// the `FERRILATOR_PLUSARGS` environment variable separated by
+    /// whitespace.
+    pub fn args(&self) -> Vec<String> {
+        match &self.args {
+            Some(args) => args.clone(),
+            None => match std::env::var("FERRILATOR_PLUSARGS") {
+                Ok(args) => args.split_whitespace().map(str::to_string).collect(),
+                Err(_) => vec![],
+            },
+        }
+    }
+
+    /// Seed the model's random numbers and randomise the initial value of
+    /// every register, for models built with `x_initial = unique`. The seed
+    /// is printed to stderr when the model is created, so a failing test can
+    /// be repeated with the same one.
+    pub fn with_seed(mut self, seed: u64) -> Config {
+        self.seed = Some(seed);
+        self
+    }
+
+    pub fn seed(&self) -> Option<u64> {
+        self.seed
+    }
+
+    /// The seed passed to Verilator, which picks a random one for 0 and
+    /// rejects any above `i32::MAX`. Seeds from 1 to `i32::MAX` are passed
+    /// as they are, others are reduced modulo `i32::MAX` with 0 becoming
+    /// `i32::MAX`.
+    pub fn verilator_seed(&self) -> Option<u32> {
+        let max = i32::MAX as u64;
+        self.seed.map(|seed| match seed % max {
+            0 => max as u32,
+            seed => seed as u32,
+        })
+    }
+
+    /// Used by generated bindings to pass the arguments to C++.
+    #[doc(hidden)]
+    pub fn c_args(&self) -> CArgs {
+        let mut args = vec!["ferrilator".to_string()];
+        if let Some(seed) = self.verilator_seed() {
+            args.push(format!("+verilator+seed+{seed}"));
+            args.push("+verilator+rand+reset+2".to_string());
+        }
+        args.extend(self.args());
+        let strings: Vec<CString> = args
+            .into_iter()
+            .map(|arg| match CString::new(arg) {
+                Ok(arg) => arg,
+                Err(e) => panic!("argument contains a nul byte: {e}"),
+            })
+            .collect();
+        let pointers = strings.iter().map(|arg| arg.as_ptr()).collect();
+        CArgs { strings, pointers }
+    }
+}
+
+/// Arguments as a C `argc` and `argv`, the first being a program name.
+#[doc(hidden)]
+pub struct CArgs {
+    // owns the strings the pointers refer to
+    #[allow(dead_code)]
+    strings: Vec<CString>,
+    pointers: Vec<*const c_char>,
+}
+
+impl CArgs {
+    pub fn argc(&self) -> i32 {
+        self.pointers.len() as i32
+    }
+
+    pub fn argv(&self) -> *const *const c_char {
+        self.pointers.as_ptr()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CStr;
+
+    #[test]
+    fn config_args() {
+        let config = Config::new()
+            .with_threads(2)
+            .with_args(&["+seed=5", "+verbose"]);
+        assert_eq!(2, config.threads());
+        assert_eq!(vec!["+seed=5", "+verbose"], config.args());
+
+        let args = config.c_args();
+        assert_eq!(3, args.argc());
+        let argv: Vec<_> = (0..args.argc() as usize)
+            .map(|i| unsafe { CStr::from_ptr(*args.argv().add(i)) })
+            .collect();
+        assert_eq!(vec![c"ferrilator", c"+seed=5", c"+verbose"], argv);
+
+        let args = Config::new().with_seed(42).with_args(&["+a"]).c_args();
+        assert_eq!(4, args.argc());
+        let argv: Vec<_> = (0..args.argc() as usize)
+            .map(|i| unsafe { CStr::from_ptr(*args.argv().add(i)) })
+            .collect();
+        assert_eq!(
+            vec![
+                c"ferrilator",
+                c"+verilator+seed+42",
+                c"+verilator+rand+reset+2",
+                c"+a"
+            ],
+            argv
+        );
+
+        // explicit arguments, even none, take the place of the environment
+        assert!(Config::new().with_args(&[]).args().is_empty());
+    }
+
+    #[test]
+    fn config_seed() {
+        assert_eq!(None, Config::new().seed());
+        assert_eq!(None, Config::new().verilator_seed());
+        let seeds = |seed| {
+            let config = Config::new().with_seed(seed);
+            (config.seed().unwrap(), config.verilator_seed().unwrap())
+        };
+        assert_eq!((7, 7), seeds(7));
+        let max = i32::MAX as u64;
+        assert_eq!((max, max as u32), seeds(max));
+        assert_eq!((0, max as u32), seeds(0));
+        assert_eq!((max + 1, 1), seeds(max + 1));
+        assert_eq!((u64::MAX, 3), seeds(u64::MAX));
+    }
+
+    #[test]
+    fn config_env_args() {
+        // no other test reads or writes this variable
+        unsafe { std::env::set_var("FERRILATOR_PLUSARGS", " +verbose  +seed=7 ") };
+        let args = Config::new().args();
+        unsafe { std::env::remove_var("FERRILATOR_PLUSARGS") };
+        assert_eq!(vec!["+verbose", "+seed=7"], args);
+        assert!(Config::new().args().is_empty());
+    }
+}
@@ -0,0 +1,267 @@
+//! Coverage from designs built with the `coverage` option. Each test writes
+//! the coverage of its model, and [`collect`] merges the files written
+//! during a `cargo test` run into one database under `target/`, which
+//! `verilator_coverage` can annotate or report on.
+//!
+//! ```ignore
+//! let mut dut = Counter::new();
+//! dut.tick_n(100);
+//! ferrilator::coverage::collect("test_counter", |path| dut.write_coverage(path)).unwrap();
+//! ```
+
+use crate::save::c_path;
+use ferrilator_core::err;
+use std::collections::HashMap;
+use std::ffi::c_char;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// The first line Verilator writes to a coverage file.
+const HEADER: &str = "# SystemC::Coverage-3";
+
+/// Used by generated bindings to call the C++ coverage writer. Verilator
+/// only prints a warning when it cannot open the file, so the file is
+/// removed first and checked for afterwards.
+#[doc(hidden)]
+pub fn write(path: &Path, write: impl FnOnce(*const c_char)) -> err::Result<()> {
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let c_path = c_path(path)?;
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    write(c_path.as_ptr());
+    if !path.exists() {
+        return err::io!("cannot write coverage to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Directory for coverage files, `coverage` in the cargo target directory.
+pub fn dir() -> PathBuf {
+    target_dir().join("coverage")
+}
+
+/// Call `write` with a path for the coverage of the test `name`, then merge
+/// every test's coverage into `coverage.dat` in the target directory and
+/// return its path. The merged file is kept up to date as each test
+/// finishes, so it covers the whole run once `cargo test` is done. Files
+/// left by earlier runs are removed by the first call of a run.
+pub fn collect(name: &str, write: impl FnOnce(&Path) -> err::Result<()>) -> err::Result<PathBuf> {
+    // tests in one binary run on threads of one process
+    static MERGING: Mutex<bool> = Mutex::new(false);
+
+    let dir = dir();
+    let mut started = MERGING.lock().unwrap_or_else(|e| e.into_inner());
+    if !*started {
+        start_run(&dir, run_id())?;
+        *started = true;
+    }
+    drop(started);
+    write(&dir.join(format!("{name}.dat")))?;
+
+    let _guard = MERGING.lock().unwrap_or_else(|e| e.into_inner());
+    let mut inputs = vec![];
+    for entry in std::fs::read_dir(&dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "dat") {
+            inputs.push(path);
+        }
+    }
+    inputs.sort();
+    let output = target_dir().join("coverage.dat");
+    merge(&inputs, &output)?;
+    Ok(output)
+}
+
+/// Tells runs apart, from `FERRILATOR_COVERAGE_RUN` when it is set, else
+/// from the cargo process that started the test binaries. Test binaries
+/// run some other way have no run, so they add to the files already there.
+fn run_id() -> Option<String> {
+    if let Ok(run) = std::env::var("FERRILATOR_COVERAGE_RUN") {
+        return Some(run);
+    }
+    cargo_run_id()
+}
+
+/// The test binaries of one `cargo test` are all started by the same cargo
+/// process, which sets `CARGO` in their environment.
+#[cfg(unix)]
+fn cargo_run_id() -> Option<String> {
+    std::env::var_os("CARGO")?;
+    Some(format!("cargo {}", std::os::unix::process::parent_id()))
+}
+
+#[cfg(not(unix))]
+fn cargo_run_id() -> Option<String> {
+    None
+}
+
+/// Remove the coverage files of any earlier run, recorded in a `run` file
+/// next to them, unless they belong to `run`. Without a run nothing is
+/// removed.
+fn start_run(dir: &Path, run: Option<String>) -> err::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let Some(run) = run else {
+        return Ok(());
+    };
+    let marker = dir.join("run");
+    if std::fs::read_to_string(&marker).is_ok_and(|previous| previous == run) {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "dat") {
+            std::fs::remove_file(path)?;
+        }
+    }
+    std::fs::write(marker, run)?;
+    Ok(())
+}
+
+/// Merge coverage files, adding the counts of points that appear in more
+/// than one.
+pub fn merge(inputs: &[PathBuf], output: &Path) -> err::Result<()> {
+    let mut points = Points::default();
+    for input in inputs {
+        let text = std::fs::read_to_string(input)?;
+        points.add(&text, input)?;
+    }
+    // write then rename so readers never see a partial file
+    let partial = output.with_extension("dat.partial");
+    std::fs::write(&partial, points.to_string())?;
+    std::fs::rename(&partial, output)?;
+    Ok(())
+}
+
+/// Coverage points in the order first seen, with their total counts.
+#[derive(Default)]
+struct Points {
+    keys: Vec<String>,
+    counts: HashMap<String, u64>,
+}
+
+impl Points {
+    /// Add the points of one coverage file, lines like `C '<key>' 12`.
+    fn add(&mut self, text: &str, path: &Path) -> err::Result<()> {
+        for (index, line) in text.lines().enumerate() {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let point = line
+                .strip_prefix("C '")
+                .and_then(|rest| rest.rsplit_once("' "))
+                .and_then(|(key, count)| Some((key, count.trim().parse::<u64>().ok()?)));
+            let Some((key, count)) = point else {
+                return err::input!(
+                    "{}:{}: unrecognised coverage line '{line}'",
+                    path.display(),
+                    index + 1
+                );
+            };
+            match self.counts.get_mut(key) {
+                Some(total) => *total += count,
+                None => {
+                    self.keys.push(key.to_string());
+                    self.counts.insert(key.to_string(), count);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for Points {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "{HEADER}")?;
+        for key in &self.keys {
+            writeln!(f, "C '{key}' {}", self.counts[key])?;
+        }
+        Ok(())
+    }
+}
+
+/// The cargo target directory, from `CARGO_TARGET_DIR` or else found from
+/// the test executable, which cargo puts in `target/<profile>/deps`.
+fn target_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("CARGO_TARGET_DIR") {
+        return PathBuf::from(dir);
+    }
+    if let Ok(exe) = std::env::current_exe()
+        && let Some(dir) = exe.ancestors().find(|dir| dir.ends_with("deps"))
+        && let Some(target) = dir.parent().and_then(|profile| profile.parent())
+    {
+        return target.to_path_buf();
+    }
+    PathBuf::from("target")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: &str = "# SystemC::Coverage-3\n\
+        C '\x01f\x02counter.sv\x01l\x0210\x01page\x02v_line/counter' 4\n\
+        C '\x01f\x02counter.sv\x01l\x0212\x01page\x02v_line/counter' 0\n";
+    const SECOND: &str = "# SystemC::Coverage-3\n\
+        C '\x01f\x02counter.sv\x01l\x0212\x01page\x02v_line/counter' 3\n\
+        C '\x01f\x02counter.sv\x01l\x0215\x01o\x02it's\x01page\x02v_user/counter' 1\n";
+
+    #[test]
+    fn coverage_merge() -> err::Result<()> {
+        let dir = std::env::temp_dir().join(format!("ferrilator-cov-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let first = dir.join("first.dat");
+        let second = dir.join("second.dat");
+        std::fs::write(&first, FIRST)?;
+        std::fs::write(&second, SECOND)?;
+
+        let output = dir.join("merged.dat");
+        merge(&[first, second], &output)?;
+        let merged = std::fs::read_to_string(&output)?;
+        std::fs::remove_dir_all(&dir)?;
+
+        assert_eq!(
+            "# SystemC::Coverage-3\n\
+            C '\x01f\x02counter.sv\x01l\x0210\x01page\x02v_line/counter' 4\n\
+            C '\x01f\x02counter.sv\x01l\x0212\x01page\x02v_line/counter' 3\n\
+            C '\x01f\x02counter.sv\x01l\x0215\x01o\x02it's\x01page\x02v_user/counter' 1\n",
+            merged
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn coverage_start_run() -> err::Result<()> {
+        let dir = std::env::temp_dir().join(format!("ferrilator-run-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        let stale = dir.join("stale.dat");
+        std::fs::write(&stale, FIRST)?;
+
+        start_run(&dir, Some("1".to_string()))?;
+        assert!(!stale.exists());
+        let fresh = dir.join("fresh.dat");
+        std::fs::write(&fresh, FIRST)?;
+        start_run(&dir, Some("1".to_string()))?;
+        assert!(fresh.exists());
+        start_run(&dir, None)?;
+        assert!(fresh.exists());
+        start_run(&dir, Some("2".to_string()))?;
+        assert!(!fresh.exists());
+
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn coverage_errors() {
+        let mut points = Points::default();
+        let path = Path::new("bad.dat");
+        assert!(points.add("C 'key' many\n", path).is_err());
+        assert!(points.add("X 'key' 1\n", path).is_err());
+        assert!(points.add("C 'key'\n", path).is_err());
+        assert!(write(Path::new("a\0b"), |_| {}).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+//! Support for functions marked with `ferrilator::attr::dpi_export`, which
+//! implement the `import "DPI-C"` functions of a design in Rust.
+//!
+//! ```ignore
+//! // import "DPI-C" function longint unsigned ref_mul(int unsigned a, int unsigned b);
+//! #[dpi_export]
+//! fn ref_mul(a: u32, b: u32) -> u64 {
+//!     a as u64 * b as u64
+//! }
+//! ```
+//!
+//! Arguments map to the DPI types as follows, outputs and inouts are taken
+//! by `&mut` and open arrays like `int values[]` as slices.
+//!
+//! | SystemVerilog          | Rust                 |
+//! |------------------------|----------------------|
+//! | `bit`, `logic`         | `bool`               |
+//! | `byte`                 | `i8`, `u8`           |
+//! | `shortint`             | `i16`, `u16`         |
+//! | `int`                  | `i32`, `u32`         |
+//! | `longint`              | `i64`, `u64`         |
+//! | `shortreal`, `real`    | `f32`, `f64`         |
+//! | `bit [N-1:0]`          | `#[bits(N)] u128`    |
+//! | `logic [N-1:0]`        | `Logic<N>`           |
+//! | `string`               | `&str`               |
+//!
+//! Packed vectors are passed as arrays of 32 bit words, so the width of a
+//! `u128` argument must be given with `#[bits(N)]`, which only `N / 32`
+//! words, rounded up, are copied for. `Logic<N>` carries its width and
+//! takes the 4-state `logic` vectors, with Z read as X. Both hold at most
+//! 128 bits.
+//!
+//! ```ignore
+//! // import "DPI-C" function bit ref_parity(input bit [95:0] data);
+//! #[dpi_export]
+//! fn ref_parity(#[bits(96)] data: u128) -> bool {
+//!     data.count_ones() % 2 == 1
+//! }
+//! ```
+
+use crate::Logic;
+use std::ffi::c_int;
+use std::ffi::c_void;
+
+/// An `svOpenArrayHandle`.
+pub type OpenArrayHandle = *const c_void;
+
+/// A word of an `svLogicVecVal` array. A bit set in `bval` is X if the same
+/// bit of `aval` is set and Z otherwise.
+#[doc(hidden)]
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogicVecVal {
+    pub aval: u32,
+    pub bval: u32,
+}
+
+/// The bits of a vector `bits` wide.
+fn mask(bits: u32) -> u128 {
+    if bits >= 128 {
+        u128::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// Read a packed vector of `bits` bits, at most 128, from an `svBitVecVal`
+/// array. Bits above the width are undefined in DPI and read as zero.
+///
+/// # Safety
+/// `words` must point to `bits.div_ceil(32)` readable words.
+#[doc(hidden)]
+pub unsafe fn read_wide(words: *const u32, bits: u32) -> u128 {
+    let words = unsafe { std::slice::from_raw_parts(words, bits.div_ceil(32) as usize) };
+    let value = words
+        .iter()
+        .rev()
+        .fold(0, |value, word| (value << 32) | *word as u128);
+    value & mask(bits)
+}
+
+/// Write a packed vector of `bits` bits, at most 128, to an `svBitVecVal`
+/// array. Bits of `value` above the width are dropped.
+///
+/// # Safety
+/// `words` must point to `bits.div_ceil(32)` writable words.
+#[doc(hidden)]
+pub unsafe fn write_wide(words: *mut u32, bits: u32, value: u128) {
+    let words = unsafe { std::slice::from_raw_parts_mut(words, bits.div_ceil(32) as usize) };
+    let value = value & mask(bits);
+    for (index, word) in words.iter_mut().enumerate() {
+        *word = (value >> (32 * index)) as u32;
+    }
+}
+
+/// Read a 4-state vector from an `svLogicVecVal` array.
+///
+/// # Safety
+/// `words` must point to `N.div_ceil(32)` readable words.
+#[doc(hidden)]
+pub unsafe fn read_logic<const N: u32>(words: *const LogicVecVal) -> Logic<N> {
+    let words = unsafe { std::slice::from_raw_parts(words, N.div_ceil(32) as usize) };
+    let (bits, x_mask) = words.iter().rev().fold((0, 0), |(bits, x_mask), word| {
+        (
+            (bits << 32) | word.aval as u128,
+            (x_mask << 32) | word.bval as u128,
+        )
+    });
+    Logic::with_x(bits, x_mask)
+}
+
+/// Write a 4-state vector to an `svLogicVecVal` array, unknown bits as X.
+///
+/// # Safety
+/// `words` must point to `N.div_ceil(32)` writable words.
+#[doc(hidden)]
+pub unsafe fn write_logic<const N: u32>(words: *mut LogicVecVal, value: Logic<N>) {
+    let words = unsafe { std::slice::from_raw_parts_mut(words, N.div_ceil(32) as usize) };
+    let bits = value.bits() | value.x_mask();
+    for (index, word) in words.iter_mut().enumerate() {
+        word.aval = (bits >> (32 * index)) as u32;
+        word.bval = (value.x_mask() >> (32 * index)) as u32;
+    }
+}
+
+/// The elements of a one dimensional open array.
+///
+/// # Safety
+/// `handle` must be an open array of `T` passed by the Verilated model.
+#[doc(hidden)]
+pub unsafe fn open_array<'a, T>(handle: OpenArrayHandle) -> &'a [T] {
+    let (data, len) = unsafe { array_parts::<T>(handle) };
+    if len == 0 {
+        return &[];
+    }
+    unsafe { std::slice::from_raw_parts(data, len) }
+}
+
+/// The elements of a one dimensional `output` or `inout` open array.
+///
+/// # Safety
+/// `handle` must be an open array of `T` passed by the Verilated model.
+#[doc(hidden)]
+pub unsafe fn open_array_mut<'a, T>(handle: OpenArrayHandle) -> &'a mut [T] {
+    let (data, len) = unsafe { array_parts::<T>(handle) };
+    if len == 0 {
+        return &mut [];
+    }
+    unsafe { std::slice::from_raw_parts_mut(data, len) }
+}
+
+/// The data and length of an open array. Generic so that the DPI runtime is
+/// only referenced from crates that use it, which link the model.
+unsafe fn array_parts<T>(handle: OpenArrayHandle) -> (*mut T, usize) {
+    unsafe extern "C" {
+        fn svGetArrayPtr(handle: OpenArrayHandle) -> *mut c_void;
+        fn svSize(handle: OpenArrayHandle, dimension: c_int) -> c_int;
+    }
+    let len = unsafe { svSize(handle, 1) };
+    let data = unsafe { svGetArrayPtr(handle) };
+    if data.is_null() || len <= 0 {
+        return (std::ptr::null_mut(), 0);
+    }
+    (data as *mut T, len as usize)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dpi_wide() {
+        let mut words = [0u32; 4];
+        let value = 0x0123_4567_89ab_cdef_fedc_ba98_7654_3210u128;
+        unsafe { write_wide(words.as_mut_ptr(), 128, value) };
+        assert_eq!([0x7654_3210, 0xfedc_ba98, 0x89ab_cdef, 0x0123_4567], words);
+        assert_eq!(value, unsafe { read_wide(words.as_ptr(), 128) });
+
+        // only the words of the vector are touched
+        let mut words = [0xffff_ffffu32; 4];
+        unsafe { write_wide(words.as_mut_ptr(), 40, value) };
+        assert_eq!([0x7654_3210, 0x98, 0xffff_ffff, 0xffff_ffff], words);
+        assert_eq!(0x98_7654_3210, unsafe { read_wide(words.as_ptr(), 40) });
+        let word = [0x8000_0001u32];
+        assert_eq!(1, unsafe { read_wide(word.as_ptr(), 31) });
+    }
+
+    #[test]
+    fn dpi_logic() {
+        let mut words = [LogicVecVal::default(); 3];
+        let value = Logic::<40>::with_x(0x12_3456_789a, 0x10_0000_0003);
+        unsafe { write_logic(words.as_mut_ptr(), value) };
+        assert_eq!(
+            [
+                LogicVecVal {
+                    aval: 0x3456_789b,
+                    bval: 0x3
+                },
+                LogicVecVal {
+                    aval: 0x12,
+                    bval: 0x10
+                },
+                LogicVecVal::default(),
+            ],
+            words
+        );
+        assert_eq!(value, unsafe { read_logic::<40>(words.as_ptr()) });
+
+        // Z reads as X
+        let word = [LogicVecVal {
+            aval: 0b01,
+            bval: 0b10,
+        }];
+        assert_eq!(
+            "2'bx1",
+            unsafe { read_logic::<2>(word.as_ptr()) }.to_string()
+        );
+    }
+}
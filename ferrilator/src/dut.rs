@@ -0,0 +1,121 @@
+use crate::PortInfo;
+use crate::Value;
+use crate::save::Snapshot;
+use ferrilator_core::err;
+use std::path::Path;
+
+/// Implemented by every struct marked with the `ferrilate` attribute, so
+/// helpers can be written once and used with any design.
+pub trait Dut {
+    /// Evaluate the model, call after changing inputs.
+    fn eval(&mut self);
+
+    /// The simulation time in picoseconds.
+    fn time(&self) -> u64;
+
+    /// The number of active edges of the first clock so far, zero for
+    /// designs without a clock.
+    fn cycle(&self) -> u64 {
+        0
+    }
+
+    /// Run the `final` blocks of the design. The model should not be
+    /// evaluated afterwards.
+    fn finish(&mut self);
+
+    /// Every port of the design, in declaration order.
+    fn ports(&self) -> &[PortInfo];
+
+    /// Drive an input port by name. The value must fit the port width.
+    fn set_port(&mut self, name: &str, value: Value) -> err::Result<()>;
+
+    /// Read a port by name. Inputs read back the value last driven.
+    fn get_port(&self, name: &str) -> err::Result<Value>;
+}
+
+/// Implemented by designs with a `clock` field.
+pub trait Clocked: Dut {
+    /// Drive the clock high and evaluate.
+    fn posedge(&mut self);
+
+    /// Drive the clock low and evaluate.
+    fn negedge(&mut self);
+
+    /// Drive the clock through one full period, evaluating on each edge.
+    /// The active edge comes first, so outputs can be sampled and inputs
+    /// driven after `tick` returns, half a period ahead of the next one.
+    fn tick(&mut self);
+
+    /// Call `tick` `n` times.
+    fn tick_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+}
+
+/// Implemented by designs with at least one clock, advances simulation time
+/// from one clock edge to the next.
+pub trait Scheduled: Dut {
+    /// The names of the clock ports, in declaration order.
+    fn clocks(&self) -> &[&'static str];
+
+    /// Advance time to the next edge of any clock, drive every clock with an
+    /// edge at that time and evaluate.
+    fn step(&mut self);
+
+    /// Step through every clock edge in the next `ns` nanoseconds, leaving
+    /// the time `ns` later.
+    fn run_for(&mut self, ns: u64);
+}
+
+/// Implemented by designs built with the `savable` option, whose state can
+/// be saved and restored with Verilator's `VerilatedSave`. The simulation
+/// time and clock schedule are saved alongside the model.
+pub trait Savable: Dut {
+    /// Save the state of the design to a file.
+    fn save(&self, path: &Path) -> err::Result<()>;
+
+    /// Restore the state saved by `save`. The file must come from the same
+    /// build of the design.
+    fn restore(&mut self, path: &Path) -> err::Result<()>;
+
+    /// Save the state of the design in memory.
+    fn snapshot(&self) -> err::Result<Snapshot> {
+        Snapshot::capture(|path| self.save(path))
+    }
+
+    /// Restore the state held by `snapshot`.
+    fn restore_snapshot(&mut self, snapshot: &Snapshot) -> err::Result<()> {
+        snapshot.apply(|path| self.restore(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ferrilator_core::dpi::TRAIT_METHODS;
+
+    /// DPI export methods would shadow the default methods, so they are
+    /// skipped by name.
+    #[test]
+    fn dut_default_methods_skip_exports() {
+        let file: syn::File = syn::parse_str(include_str!("dut.rs")).unwrap();
+        for item in file.items {
+            let syn::Item::Trait(item) = item else {
+                continue;
+            };
+            for method in item.items {
+                if let syn::TraitItem::Fn(method) = method
+                    && method.default.is_some()
+                {
+                    let name = method.sig.ident.to_string();
+                    assert!(
+                        TRAIT_METHODS.contains(&name.as_str()),
+                        "{}::{name} is missing from ferrilator_core::dpi::TRAIT_METHODS",
+                        item.ident
+                    );
+                }
+            }
+        }
+    }
+}
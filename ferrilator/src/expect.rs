@@ -0,0 +1,206 @@
+//! Assertions that report the state of the design when they fail. The
+//! [`expect!`](crate::expect!) and [`expect_eq!`](crate::expect_eq!) macros
+//! work like `assert!` and `assert_eq!`, taking the design as their first
+//! argument, and add the cycle, the simulation time and the value of every
+//! port to the panic message.
+//!
+//! ```ignore
+//! for cycle in 0..10 {
+//!     dut.tick();
+//!     ferrilator::expect_eq!(dut, dut.get_value(), cycle);
+//! }
+//! ```
+
+use crate::Dut;
+
+/// The cycle, time and value of every port of `dut`, one port per line,
+/// as added to the message of a failed expectation.
+pub fn context<D: Dut + ?Sized>(dut: &D) -> String {
+    let mut text = format!("at cycle {}, time {} ps", dut.cycle(), dut.time());
+    let name_width = dut
+        .ports()
+        .iter()
+        .map(|port| port.name().len())
+        .max()
+        .unwrap_or(0);
+    for port in dut.ports() {
+        let value = match dut.get_port(port.name()) {
+            Ok(value) => value.to_string(),
+            Err(_) => "?".to_string(),
+        };
+        text.push_str(&format!("\n  {:name_width$}  {value}", port.name()));
+    }
+    text
+}
+
+/// Used by the expect macros, which call it as a method so the design can
+/// be passed by value or by reference.
+#[doc(hidden)]
+pub trait Failed {
+    fn expect_failed(&self, message: std::fmt::Arguments) -> !;
+}
+
+impl<D: Dut + ?Sized> Failed for D {
+    #[track_caller]
+    fn expect_failed(&self, message: std::fmt::Arguments) -> ! {
+        panic!("{message}\n{}", context(self))
+    }
+}
+
+/// Panics if the condition is false, like `assert!`, and reports the state
+/// of the design given as the first argument.
+///
+/// ```ignore
+/// ferrilator::expect!(dut, dut.get_ready());
+/// ferrilator::expect!(dut, dut.get_ready(), "not ready after {} requests", count);
+/// ```
+#[macro_export]
+macro_rules! expect {
+    ($dut:expr, $cond:expr $(,)?) => {
+        if !$cond {
+            use $crate::expect::Failed as _;
+            ($dut).expect_failed(::std::format_args!(
+                "expectation failed: {}",
+                ::std::stringify!($cond)
+            ));
+        }
+    };
+    ($dut:expr, $cond:expr, $($arg:tt)+) => {
+        if !$cond {
+            use $crate::expect::Failed as _;
+            ($dut).expect_failed(::std::format_args!($($arg)+));
+        }
+    };
+}
+
+/// Panics if the two values differ, like `assert_eq!`, and reports the
+/// state of the design given as the first argument.
+///
+/// ```ignore
+/// ferrilator::expect_eq!(dut, dut.get_value(), 3);
+/// ferrilator::expect_eq!(dut, dut.get_value(), 3, "after {} ticks", ticks);
+/// ```
+#[macro_export]
+macro_rules! expect_eq {
+    ($dut:expr, $left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    use $crate::expect::Failed as _;
+                    ($dut).expect_failed(::std::format_args!(
+                        "expectation `{} == {}` failed\n  left: {:?}\n right: {:?}",
+                        ::std::stringify!($left),
+                        ::std::stringify!($right),
+                        left,
+                        right
+                    ));
+                }
+            }
+        }
+    };
+    ($dut:expr, $left:expr, $right:expr, $($arg:tt)+) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    use $crate::expect::Failed as _;
+                    ($dut).expect_failed(::std::format_args!(
+                        "expectation `{} == {}` failed: {}\n  left: {:?}\n right: {:?}",
+                        ::std::stringify!($left),
+                        ::std::stringify!($right),
+                        ::std::format_args!($($arg)+),
+                        left,
+                        right
+                    ));
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clocked;
+    use crate::PortInfo;
+    use crate::mock::Mock;
+
+    const PORTS: [PortInfo; 2] = [
+        PortInfo::new("enable", 1, false, true, false),
+        PortInfo::new("count", 16, false, false, true),
+    ];
+
+    /// Counts cycles, after two of them.
+    fn ticker() -> Mock {
+        let mut dut = Mock::new(&PORTS).on_posedge(|dut| dut.set("count", dut.get("count") + 1));
+        dut.set("enable", 1);
+        dut.tick_n(2);
+        dut
+    }
+
+    fn panic_message(f: impl FnOnce() + std::panic::UnwindSafe) -> String {
+        let payload = std::panic::catch_unwind(f).unwrap_err();
+        payload.downcast_ref::<String>().unwrap().clone()
+    }
+
+    #[test]
+    fn expect_context() {
+        assert_eq!(
+            "at cycle 2, time 20 ps\n  enable  1\n  count   0x2",
+            context(&ticker())
+        );
+    }
+
+    #[test]
+    fn expect_passes() {
+        let mut dut = ticker();
+        crate::expect!(dut, dut.get("count") == 2);
+        crate::expect_eq!(dut, dut.get("count"), 2, "count");
+
+        // the design can be passed by reference too
+        let dut = &mut dut;
+        dut.tick();
+        crate::expect!(dut, dut.get("count") == 3, "count");
+        crate::expect_eq!(dut, dut.get("count"), 3);
+    }
+
+    #[test]
+    fn expect_failures() {
+        let message = panic_message(|| {
+            let dut = ticker();
+            crate::expect!(dut, dut.get("count") > 2);
+        });
+        assert_eq!(
+            "expectation failed: dut.get(\"count\") > 2\nat cycle 2, time 20 ps\n  enable  1\n  count   0x2",
+            message
+        );
+
+        let message = panic_message(|| {
+            let dut = ticker();
+            crate::expect!(&dut, dut.get("count") > 2, "count is {}", dut.get("count"));
+        });
+        assert!(message.starts_with("count is 2\nat cycle 2"), "{message}");
+
+        let message = panic_message(|| {
+            let dut = ticker();
+            crate::expect_eq!(dut, dut.get("count"), 3);
+        });
+        assert!(
+            message.starts_with(
+                "expectation `dut.get(\"count\") == 3` failed\n  left: 2\n right: 3\nat cycle 2"
+            ),
+            "{message}"
+        );
+
+        let message = panic_message(|| {
+            let mut dut = ticker();
+            let dut = &mut dut;
+            crate::expect_eq!(dut, dut.get("count"), 3, "after {} ticks", 2);
+        });
+        assert!(
+            message.starts_with(
+                "expectation `dut.get(\"count\") == 3` failed: after 2 ticks\n  left: 2"
+            ),
+            "{message}"
+        );
+    }
+}
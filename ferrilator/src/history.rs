@@ -0,0 +1,211 @@
+use crate::Dut;
+use crate::PortInfo;
+use crate::Value;
+use ferrilator_core::err;
+use std::collections::VecDeque;
+
+/// The value of every port over the last few cycles of a design. Generated
+/// bindings with a single clock record one after each `tick` once
+/// `record_history` is called, and print it if the design is dropped by a
+/// panic, such as a failed assertion.
+#[derive(Clone, Debug, PartialEq)]
+pub struct History {
+    depth: usize,
+    ports: Vec<PortInfo>,
+    samples: VecDeque<Sample>,
+}
+
+/// The ports of a design at one point in time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    cycle: u64,
+    time: u64,
+    values: Vec<Value>,
+}
+
+impl History {
+    /// Keep the last `depth` samples.
+    pub fn new(depth: usize) -> History {
+        assert!(depth > 0, "a history must keep at least one sample");
+        History {
+            depth,
+            ports: vec![],
+            samples: VecDeque::with_capacity(depth),
+        }
+    }
+
+    /// Sample every port of `dut`, dropping the oldest sample when full.
+    pub fn record<D: Dut + ?Sized>(&mut self, dut: &D) {
+        if self.ports.is_empty() {
+            self.ports = dut.ports().to_vec();
+        }
+        let values = self
+            .ports
+            .iter()
+            .map(|port| {
+                dut.get_port(port.name())
+                    .unwrap_or(Value::new(0, port.width()))
+            })
+            .collect();
+        if self.samples.len() == self.depth {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(Sample {
+            cycle: dut.cycle(),
+            time: dut.time(),
+            values,
+        });
+    }
+
+    /// The ports sampled, empty until the first sample.
+    pub fn ports(&self) -> &[PortInfo] {
+        &self.ports
+    }
+
+    /// The samples held, oldest first.
+    pub fn samples(&self) -> impl Iterator<Item = &Sample> {
+        self.samples.iter()
+    }
+
+    /// The values of one port, oldest first.
+    pub fn values(&self, port: &str) -> err::Result<Vec<Value>> {
+        let Some(index) = self.ports.iter().position(|info| info.name() == port) else {
+            return err::input!("no port named '{port}' in the history");
+        };
+        Ok(self
+            .samples
+            .iter()
+            .map(|sample| sample.values[index])
+            .collect())
+    }
+
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Print the history to stderr if the thread is panicking, called when
+    /// a design is dropped.
+    #[doc(hidden)]
+    pub fn print_if_panicking(&self) {
+        if std::thread::panicking() && !self.is_empty() {
+            eprintln!("{self}");
+        }
+    }
+}
+
+impl Sample {
+    pub fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    /// The simulation time in picoseconds.
+    pub fn time(&self) -> u64 {
+        self.time
+    }
+
+    /// The value of each port, in the order of [`History::ports`].
+    pub fn values(&self) -> &[Value] {
+        &self.values
+    }
+}
+
+impl std::fmt::Display for History {
+    /// A table with a row per port and a column per cycle.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "history of the last {} cycles", self.samples.len())?;
+        let mut rows = vec![(
+            "cycle",
+            self.samples
+                .iter()
+                .map(|sample| sample.cycle.to_string())
+                .collect(),
+        )];
+        for (index, port) in self.ports.iter().enumerate() {
+            let values: Vec<String> = self
+                .samples
+                .iter()
+                .map(|sample| sample.values[index].to_string())
+                .collect();
+            rows.push((port.name(), values));
+        }
+
+        let name_width = rows.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        let widths: Vec<usize> = (0..self.samples.len())
+            .map(|column| rows.iter().map(|(_, row)| row[column].len()).max().unwrap())
+            .collect();
+        for (name, row) in &rows {
+            write!(f, "\n{name:name_width$}")?;
+            for (text, width) in row.iter().zip(&widths) {
+                write!(f, "  {text:>width$}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clocked;
+    use crate::mock::Mock;
+
+    const PORTS: [PortInfo; 2] = [
+        PortInfo::new("start", 1, false, true, false),
+        PortInfo::new("value", 32, false, false, true),
+    ];
+
+    /// Doubles `value` on each cycle, starting from one.
+    fn doubler() -> Mock {
+        let mut dut = Mock::new(&PORTS).on_posedge(|dut| dut.set("value", dut.get("value") * 2));
+        dut.set("value", 1);
+        dut
+    }
+
+    #[test]
+    fn history_record() -> err::Result<()> {
+        let mut dut = doubler();
+        let mut history = History::new(3);
+        assert!(history.is_empty());
+        assert!(history.ports().is_empty());
+
+        for _ in 0..5 {
+            dut.tick();
+            history.record(&dut);
+        }
+        assert_eq!(3, history.len());
+        assert_eq!(&PORTS, history.ports());
+        let cycles: Vec<_> = history.samples().map(Sample::cycle).collect();
+        assert_eq!(vec![3, 4, 5], cycles);
+        let last = history.samples().last().unwrap();
+        assert_eq!(50, last.time());
+        assert_eq!(&[Value::from(false), Value::from(32u32)], last.values());
+        assert_eq!(
+            vec![Value::from(8u32), Value::from(16u32), Value::from(32u32)],
+            history.values("value")?
+        );
+        assert!(history.values("other").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn history_display() {
+        let mut dut = doubler();
+        let mut history = History::new(4);
+        history.record(&dut);
+        for _ in 0..4 {
+            dut.tick();
+            history.record(&dut);
+        }
+        assert_eq!(
+            "history of the last 4 cycles\n\
+             cycle    1    2    3     4\n\
+             start    0    0    0     0\n\
+             value  0x2  0x4  0x8  0x10",
+            history.to_string()
+        );
+    }
+}
@@ -1,10 +1,51 @@
 pub mod attr {
+    pub use ferrilator_macros::dpi_export;
     pub use ferrilator_macros::ferrilate;
 }
 
+pub mod coverage;
+pub mod dpi;
+pub mod expect;
+pub mod save;
+pub mod sim;
+pub mod vectors;
+pub mod wave;
+pub mod xcheck;
+
+mod config;
+mod dut;
+mod history;
+mod logic;
+#[cfg(test)]
+mod mock;
+mod packed;
+mod port;
+mod scheduler;
+mod version;
+mod watch;
+
+pub use config::Config;
+pub use dut::Clocked;
+pub use dut::Dut;
+pub use dut::Savable;
+pub use dut::Scheduled;
+pub use ferrilator_core::err;
+pub use ferrilator_macros::HwEnum;
+pub use ferrilator_macros::Packed;
+pub use history::History;
+pub use history::Sample;
+pub use logic::Logic;
+pub use packed::Packed;
+pub use port::PortInfo;
+pub use port::PortType;
+pub use port::Value;
+pub use scheduler::Scheduler;
+pub use version::Version;
+pub use watch::OnChange;
+
 use ferrilator_core::DataType;
 use ferrilator_core::Module;
-use ferrilator_core::err;
+use quote::ToTokens;
 use std::io::Read;
 use std::io::Write;
 
@@ -14,6 +55,8 @@ use std::io::Write;
 /// All file paths are relative to the crate root.
 /// Verilator is assumed to be installed at `/usr/share/verilator` but this
 /// can be overriden by setting VERILATOR_ROOT to the install location.
+/// The installed Verilator version is detected and the build steps adapted
+/// to it, see the readme for the supported versions.
 pub fn build(name: &str, rust_file: &str, verilog_files: &[&str]) -> err::Result<()> {
     for fname in verilog_files {
         if !std::fs::exists(fname)? {
@@ -21,20 +64,57 @@ pub fn build(name: &str, rust_file: &str, verilog_files: &[&str]) -> err::Result
         }
     }
 
+    let version = Version::detect()?;
+    version.check_supported()?;
+
     let item = load_struct(name, rust_file)?;
-    let module_name = read_module_name(&item)?;
-    let module = Module::from_struct(module_name.clone(), item)?;
+    let attr = ferrilate_attribute(&item)?.tokens.clone();
+    let module = Module::from_attribute(attr, item.into_token_stream())?;
+    let module_name = module.name().clone();
+    let options = module.options();
+
+    // Verilator 4 compiles the runtime differently for threaded models, and
+    // the runtimes of a crate define the same symbols, so there every model
+    // is built thread safe whether or not it has the threads option
+    let threaded = options.threads().is_some() || version.major() < 5;
+    let cxx_flags = version.cxx_flags(threaded);
+    let mut verilator_args = vec![];
+    let mut runtime_sources = version.runtime_sources(threaded).to_vec();
+    // always built, so designs can import functions from Rust
+    runtime_sources.push("verilated_dpi.cpp");
+    if options.savable() {
+        verilator_args.push("--savable".to_string());
+        runtime_sources.push("verilated_save.cpp");
+    }
+    if options.coverage() {
+        verilator_args.push("--coverage".to_string());
+        runtime_sources.push("verilated_cov.cpp");
+    }
+    if let Some(x_assign) = options.x_assign() {
+        verilator_args.push("--x-assign".to_string());
+        verilator_args.push(x_assign.to_string());
+    }
+    if let Some(x_initial) = options.x_initial() {
+        verilator_args.push("--x-initial".to_string());
+        verilator_args.push(x_initial.to_string());
+    }
+    if threaded {
+        verilator_args.push("--threads".to_string());
+        verilator_args.push(options.threads().unwrap_or(1).to_string());
+    }
 
     let out_dir = std::env::var("OUT_DIR").unwrap();
     let verilated_dir = format!("{out_dir}/{module_name}_verilated");
     let binding_src = format!("{verilated_dir}/{module_name}_binding.cc");
-    write_binding_file(&module_name, &binding_src, &module)?;
+    write_binding_file(&module_name, &binding_src, &module, version)?;
 
     check_process_output(
         "verilator",
         std::process::Command::new("verilator")
             .arg("--cc")
             .arg("--build")
+            .args(version.timing_args())
+            .args(&verilator_args)
             .args(["--top-module", &module_name])
             .args(["--Mdir", &verilated_dir])
             .args(verilog_files)
@@ -43,63 +123,108 @@ pub fn build(name: &str, rust_file: &str, verilog_files: &[&str]) -> err::Result
             .unwrap(),
     );
 
+    if options.dpi() {
+        write_dpi_file(&module, &verilated_dir, &out_dir)?;
+    }
+
+    // checked against the width of ports declared with a `PortType`
+    let header = std::fs::read_to_string(format!("{verilated_dir}/V{module_name}.h"))?;
+    for (port, width) in port::verilated_widths(&header) {
+        println!("cargo:rustc-env=FERRILATOR_WIDTH_{module_name}_{port}={width}");
+    }
+
     let verilator_root = std::env::var("VERILATOR_ROOT").unwrap_or("/usr/share/verilator".into());
     let verilator_include = format!("{verilator_root}/include");
     let binding_obj = format!("{verilated_dir}/{module_name}_binding.o");
     check_process_output(
         "build binding file",
         std::process::Command::new("g++")
-            .arg(&format!("-I{verilator_include}"))
-            .arg(&format!("-I{verilated_dir}"))
-            .args(&["-c", &binding_src])
-            .args(&["-o", &binding_obj])
+            .arg(format!("-I{verilator_include}"))
+            .arg(format!("-I{verilator_include}/vltstd"))
+            .arg(format!("-I{verilated_dir}"))
+            .args(cxx_flags)
+            .args(["-c", &binding_src])
+            .args(["-o", &binding_obj])
             .output()
             .unwrap(),
     );
 
-    let all_path = format!("{verilated_dir}/V{module_name}__ALL.a");
+    let archive_path = format!("{verilated_dir}/{}", version.module_archive(&module_name));
     let module_path = format!("{verilated_dir}/libV{module_name}.a");
-    std::fs::copy(&all_path, &module_path).unwrap();
+    if archive_path != module_path {
+        std::fs::copy(&archive_path, &module_path).unwrap();
+    }
     check_process_output(
         "archive verilator runtime",
         std::process::Command::new("ar")
             .arg("rcs")
             .arg(&module_path)
-            .arg(&format!("{verilated_dir}/{module_name}_binding.o"))
             .arg(&binding_obj)
             .output()
             .unwrap(),
     );
 
+    // named after the module, as the linker would otherwise take the first
+    // runtime of a crate for every model, whatever options it was built for
+    let runtime_path = format!("{verilated_dir}/libverilated_{module_name}.a");
     let verilated_src = format!("{verilator_include}/verilated.cpp");
-    let verilated_obj = format!("{verilated_dir}/verilated.o");
-    let runtime_path = format!("{verilated_dir}/libverilated.a");
-    if is_older(&runtime_path, &verilated_src) {
-        check_process_output(
-            "build verilator runtime",
-            std::process::Command::new("g++")
-                .arg(&format!("-I{verilator_include}"))
-                .args(&["-c", &verilated_src])
-                .args(&["-o", &verilated_obj])
-                .output()
-                .unwrap(),
-        );
+    let runtime_objs: Vec<_> = runtime_sources
+        .iter()
+        .map(|src| format!("{verilated_dir}/{}.o", src.trim_end_matches(".cpp")))
+        .collect();
+    // the sources and flags depend on the options, so rebuild when a source
+    // is missing or the flags changed
+    let flags_path = format!("{verilated_dir}/verilated_{module_name}.flags");
+    let flags = cxx_flags.join(" ");
+    if is_older(&runtime_path, &verilated_src)
+        || runtime_objs
+            .iter()
+            .any(|obj| !std::path::Path::new(obj).exists())
+        || std::fs::read_to_string(&flags_path).ok().as_ref() != Some(&flags)
+    {
+        for (src, runtime_obj) in runtime_sources.iter().zip(&runtime_objs) {
+            let runtime_src = format!("{verilator_include}/{src}");
+            check_process_output(
+                "build verilator runtime",
+                std::process::Command::new("g++")
+                    .arg(format!("-I{verilator_include}"))
+                    .arg(format!("-I{verilator_include}/vltstd"))
+                    .args(cxx_flags)
+                    .args(["-c", &runtime_src])
+                    .args(["-o", runtime_obj])
+                    .output()
+                    .unwrap(),
+            );
+        }
+        // ar keeps the members of an existing archive
+        let _ = std::fs::remove_file(&runtime_path);
         check_process_output(
             "archive verilator runtime",
             std::process::Command::new("ar")
                 .arg("rcs")
                 .arg(&runtime_path)
-                .arg(&verilated_obj)
+                .args(&runtime_objs)
                 .output()
                 .unwrap(),
         );
+        std::fs::write(&flags_path, flags)?;
     }
 
     println!("cargo:rustc-link-search=native={verilated_dir}");
     println!("cargo:rustc-link-lib=static=V{module_name}");
-    println!("cargo:rustc-link-lib=static=verilated");
+    println!("cargo:rustc-link-lib=static=verilated_{module_name}");
+    for lib in version.runtime_libs(threaded) {
+        println!("cargo:rustc-link-lib=dylib={lib}");
+    }
     println!("cargo:rustc-link-lib=dylib=stdc++");
 
+    // without a context of their own models share state, so the generated
+    // structs are only Send from 4.200
+    println!("cargo:rustc-check-cfg=cfg(ferrilator_global_context)");
+    if !version.has_context() {
+        println!("cargo:rustc-cfg=ferrilator_global_context");
+    }
+
     for fname in verilog_files {
         println!("cargo:rerun-if-changed={fname}");
     }
@@ -138,29 +263,23 @@ fn load_struct(name: &str, rust_file: &str) -> err::Result<syn::ItemStruct> {
     let file = syn::parse_file(&content)?;
 
     for item in file.items {
-        if let syn::Item::Struct(item) = item {
-            if item.ident.to_string() == name {
-                return Ok(item);
-            }
+        if let syn::Item::Struct(item) = item
+            && item.ident == name
+        {
+            return Ok(item);
         }
     }
 
     err::input!("failed to find struct defn for {name}")
 }
 
-fn read_module_name(item: &syn::ItemStruct) -> err::Result<String> {
+fn ferrilate_attribute(item: &syn::ItemStruct) -> err::Result<&syn::MetaList> {
     for attr in &item.attrs {
-        match &attr.meta {
-            syn::Meta::List(attr) => match attr.path.segments.last() {
-                Some(seg) => {
-                    if seg.ident.to_string() == "ferrilate" {
-                        return Ok(attr.tokens.to_string());
-                    }
-                }
-                None => {}
-            },
-            syn::Meta::Path(_) => {}
-            syn::Meta::NameValue(_) => {}
+        if let syn::Meta::List(attr) = &attr.meta
+            && let Some(seg) = attr.path.segments.last()
+            && seg.ident == "ferrilate"
+        {
+            return Ok(attr);
         }
     }
     err::input!(
@@ -169,73 +288,202 @@ fn read_module_name(item: &syn::ItemStruct) -> err::Result<String> {
     )
 }
 
-fn write_binding_file(module_name: &str, fname: &str, module: &Module) -> err::Result<()> {
+/// Write the methods calling the exports listed in `V{module}__Dpi.h`,
+/// which the `ferrilate` attribute includes. Verilator writes no header for
+/// designs without DPI functions.
+fn write_dpi_file(module: &Module, verilated_dir: &str, out_dir: &str) -> err::Result<()> {
+    let module_name = module.name();
+    let header_path = format!("{verilated_dir}/V{module_name}__Dpi.h");
+    let header = if std::fs::exists(&header_path)? {
+        std::fs::read_to_string(&header_path)?
+    } else {
+        String::new()
+    };
+    let (tokens, skipped) = ferrilator_core::dpi::dpi_exports(module, &header)?;
+    for (name, reason) in skipped {
+        println!("cargo:warning=DPI export {name} of {module_name} is skipped, {reason}");
+    }
+    std::fs::write(
+        format!("{out_dir}/{module_name}_dpi.rs"),
+        tokens.to_string(),
+    )?;
+    Ok(())
+}
+
+fn write_binding_file(
+    module_name: &str,
+    fname: &str,
+    module: &Module,
+    version: Version,
+) -> err::Result<()> {
     if let Some(dir) = std::path::Path::new(fname).parent() {
         std::fs::create_dir_all(dir)?;
     }
     let mut file = std::fs::File::create(fname)?;
     writeln!(file, "#include <V{module_name}.h>")?;
+    if module.options().dpi() {
+        writeln!(file, "#include <svdpi.h>")?;
+    }
+    if module.options().savable() {
+        writeln!(file, "#include <verilated_save.h>")?;
+    }
+    if module.options().coverage() {
+        writeln!(file, "#include <verilated_cov.h>")?;
+    }
     writeln!(file)?;
+
+    if version.needs_time_stamp() {
+        writeln!(file, "double sc_time_stamp() __attribute__((weak));")?;
+        writeln!(file, "double sc_time_stamp() {{")?;
+        writeln!(file, "  return 0;")?;
+        writeln!(file, "}}")?;
+        writeln!(file)?;
+    }
+
+    if module.ports().iter().any(|port| port.rust_type().is_some()) {
+        write_bits_fns(&mut file)?;
+    }
+
+    if version.has_context() {
+        write_context_handle(&mut file, module_name, module, version)?;
+    } else {
+        write_global_handle(&mut file, module_name)?;
+    }
+
     writeln!(file, "extern \"C\" {{")?;
 
-    writeln!(file, "V{module_name}* {module_name}_new() {{")?;
-    writeln!(file, "  return new V{module_name};")?;
+    writeln!(
+        file,
+        "{module_name}_handle* {module_name}_new(uint32_t threads, int argc, const char** argv) {{"
+    )?;
+    writeln!(
+        file,
+        "  return new {module_name}_handle(threads, argc, argv);"
+    )?;
     writeln!(file, "}}")?;
 
-    writeln!(file, "void {module_name}_del(V{module_name}* dut) {{")?;
-    writeln!(file, "  delete dut;")?;
+    writeln!(
+        file,
+        "void {module_name}_del({module_name}_handle* handle) {{"
+    )?;
+    writeln!(file, "  delete handle;")?;
     writeln!(file, "}}")?;
 
-    writeln!(file, "void {module_name}_eval(V{module_name}* dut) {{")?;
-    writeln!(file, "  dut->eval();")?;
+    writeln!(
+        file,
+        "void {module_name}_eval({module_name}_handle* handle) {{"
+    )?;
+    writeln!(file, "  handle->model.eval();")?;
     writeln!(file, "}}")?;
 
+    writeln!(
+        file,
+        "void {module_name}_final({module_name}_handle* handle) {{"
+    )?;
+    writeln!(file, "  handle->model.final();")?;
+    writeln!(file, "}}")?;
+
+    if module.options().savable() {
+        write_save_fns(&mut file, module_name)?;
+    }
+
+    if module.options().dpi() {
+        // exports run in the scope set by the caller, and find it through
+        // the context of the calling thread
+        writeln!(
+            file,
+            "void {module_name}_dpi_scope({module_name}_handle* handle) {{"
+        )?;
+        if version.has_context() {
+            writeln!(file, "  Verilated::threadContextp(&handle->context);")?;
+        }
+        writeln!(
+            file,
+            "  svSetScope(svGetScopeFromName(\"TOP.{module_name}\"));"
+        )?;
+        writeln!(file, "}}")?;
+    }
+
+    if module.options().coverage() {
+        writeln!(
+            file,
+            "void {module_name}_write_coverage({module_name}_handle* handle, const char* path) {{"
+        )?;
+        if version.has_context() {
+            writeln!(file, "  handle->context.coveragep()->write(path);")?;
+        } else {
+            writeln!(file, "  VerilatedCov::write(path);")?;
+        }
+        writeln!(file, "}}")?;
+    }
+
     for port in module.ports() {
         let port_name = &port.name();
+        // arrays are accessed an element at a time, `VlUnpacked` and plain
+        // arrays can both be indexed
+        let (index_param, element) = match port.array_len() {
+            Some(_) => (", size_t index", format!("{port_name}[index]")),
+            None => ("", port_name.to_string()),
+        };
 
-        if port.data_type() == DataType::U128 {
+        if port.rust_type().is_some() {
+            // the width is only known to the compiler, which picks the
+            // overload for the type Verilator gave the port
             if port.input() {
                 writeln!(
                     file,
-                    "void {module_name}_set_{port_name}(V{module_name}* dut, const uint32_t (&arr)[4]) {{"
+                    "void {module_name}_set_{port_name}({module_name}_handle* handle{index_param}, const uint32_t (&arr)[4]) {{"
                 )?;
-                writeln!(file, "  dut->{port_name}.at(0) = arr[0];")?;
-                writeln!(file, "  dut->{port_name}.at(1) = arr[1];")?;
-                writeln!(file, "  dut->{port_name}.at(2) = arr[2];")?;
-                writeln!(file, "  dut->{port_name}.at(3) = arr[3];")?;
+                writeln!(file, "  ferrilator_set_bits(handle->model.{element}, arr);")?;
                 writeln!(file, "}}")?;
             }
 
-            if port.output() {
+            writeln!(
+                file,
+                "void {module_name}_get_{port_name}({module_name}_handle* handle{index_param}, uint32_t (&arr)[4]) {{"
+            )?;
+            writeln!(file, "  ferrilator_get_bits(handle->model.{element}, arr);")?;
+            writeln!(file, "}}")?;
+        } else if port.data_type() == DataType::U128 {
+            if port.input() {
                 writeln!(
                     file,
-                    "void {module_name}_get_{port_name}(V{module_name}* dut, uint32_t (&arr)[4]) {{"
+                    "void {module_name}_set_{port_name}({module_name}_handle* handle{index_param}, const uint32_t (&arr)[4]) {{"
                 )?;
-                writeln!(file, "  arr[0] = dut->{port_name}.at(0);")?;
-                writeln!(file, "  arr[1] = dut->{port_name}.at(1);")?;
-                writeln!(file, "  arr[2] = dut->{port_name}.at(2);")?;
-                writeln!(file, "  arr[3] = dut->{port_name}.at(3);")?;
+                writeln!(file, "  handle->model.{element}.at(0) = arr[0];")?;
+                writeln!(file, "  handle->model.{element}.at(1) = arr[1];")?;
+                writeln!(file, "  handle->model.{element}.at(2) = arr[2];")?;
+                writeln!(file, "  handle->model.{element}.at(3) = arr[3];")?;
                 writeln!(file, "}}")?;
             }
+
+            // inputs can be read back too, for get_port
+            writeln!(
+                file,
+                "void {module_name}_get_{port_name}({module_name}_handle* handle{index_param}, uint32_t (&arr)[4]) {{"
+            )?;
+            writeln!(file, "  arr[0] = handle->model.{element}.at(0);")?;
+            writeln!(file, "  arr[1] = handle->model.{element}.at(1);")?;
+            writeln!(file, "  arr[2] = handle->model.{element}.at(2);")?;
+            writeln!(file, "  arr[3] = handle->model.{element}.at(3);")?;
+            writeln!(file, "}}")?;
         } else {
             let type_name = c_type_name(port.data_type());
             if port.input() {
                 writeln!(
                     file,
-                    "void {module_name}_set_{port_name}(V{module_name}* dut, {type_name} value) {{"
+                    "void {module_name}_set_{port_name}({module_name}_handle* handle{index_param}, {type_name} value) {{"
                 )?;
-                writeln!(file, "  dut->{port_name} = value;")?;
+                writeln!(file, "  handle->model.{element} = value;")?;
                 writeln!(file, "}}")?;
             }
 
-            if port.output() {
-                writeln!(
-                    file,
-                    "{type_name} {module_name}_get_{port_name}(V{module_name}* dut) {{"
-                )?;
-                writeln!(file, "  return dut->{port_name};")?;
-                writeln!(file, "}}")?;
-            }
+            writeln!(
+                file,
+                "{type_name} {module_name}_get_{port_name}({module_name}_handle* handle{index_param}) {{"
+            )?;
+            writeln!(file, "  return handle->model.{element};")?;
+            writeln!(file, "}}")?;
         }
     }
 
@@ -244,6 +492,164 @@ fn write_binding_file(module_name: &str, fname: &str, module: &Module) -> err::R
     Ok(())
 }
 
+/// Copy ports of `PortType` types to and from four words, whatever the type
+/// of the port in the model.
+fn write_bits_fns(file: &mut std::fs::File) -> err::Result<()> {
+    for ty in ["CData", "SData", "IData"] {
+        writeln!(
+            file,
+            "static inline void ferrilator_set_bits({ty}& port, const uint32_t (&arr)[4]) {{"
+        )?;
+        writeln!(file, "  port = arr[0];")?;
+        writeln!(file, "}}")?;
+        writeln!(
+            file,
+            "static inline void ferrilator_get_bits(const {ty}& port, uint32_t (&arr)[4]) {{"
+        )?;
+        writeln!(file, "  arr[0] = port;")?;
+        writeln!(file, "  arr[1] = arr[2] = arr[3] = 0;")?;
+        writeln!(file, "}}")?;
+    }
+
+    writeln!(
+        file,
+        "static inline void ferrilator_set_bits(QData& port, const uint32_t (&arr)[4]) {{"
+    )?;
+    writeln!(file, "  port = ((QData)arr[1] << 32) | arr[0];")?;
+    writeln!(file, "}}")?;
+    writeln!(
+        file,
+        "static inline void ferrilator_get_bits(const QData& port, uint32_t (&arr)[4]) {{"
+    )?;
+    writeln!(file, "  arr[0] = (uint32_t)port;")?;
+    writeln!(file, "  arr[1] = (uint32_t)(port >> 32);")?;
+    writeln!(file, "  arr[2] = arr[3] = 0;")?;
+    writeln!(file, "}}")?;
+
+    writeln!(file, "template <std::size_t N>")?;
+    writeln!(
+        file,
+        "static inline void ferrilator_set_bits(VlWide<N>& port, const uint32_t (&arr)[4]) {{"
+    )?;
+    writeln!(file, "  for (std::size_t i = 0; i < N; i++) {{")?;
+    writeln!(file, "    port.at(i) = i < 4 ? arr[i] : 0;")?;
+    writeln!(file, "  }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file, "template <std::size_t N>")?;
+    writeln!(
+        file,
+        "static inline void ferrilator_get_bits(const VlWide<N>& port, uint32_t (&arr)[4]) {{"
+    )?;
+    writeln!(file, "  for (std::size_t i = 0; i < 4; i++) {{")?;
+    writeln!(file, "    arr[i] = i < N ? port.at(i) : 0;")?;
+    writeln!(file, "  }}")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+    Ok(())
+}
+
+/// The Rust side passes its own state as `words`, saved ahead of the model.
+fn write_context_handle(
+    file: &mut std::fs::File,
+    module_name: &str,
+    module: &Module,
+    version: Version,
+) -> err::Result<()> {
+    // the thread count and arguments are set before the model is added to
+    // the context, the count cannot be lower than the model was built for
+    let built_threads = module.options().threads().unwrap_or(1);
+    writeln!(
+        file,
+        "static VerilatedContext* {module_name}_context(VerilatedContext* context, uint32_t threads, int argc, const char** argv) {{"
+    )?;
+    if version.context_threads() {
+        writeln!(
+            file,
+            "  if (threads < {built_threads}) threads = {built_threads};"
+        )?;
+        writeln!(file, "  context->threads(threads);")?;
+    }
+    writeln!(file, "  context->commandArgs(argc, argv);")?;
+    writeln!(file, "  return context;")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    // each model gets a context of its own, so models share no state and
+    // can be simulated on different threads
+    writeln!(file, "struct {module_name}_handle {{")?;
+    writeln!(file, "  VerilatedContext context;")?;
+    writeln!(file, "  V{module_name} model;")?;
+    writeln!(
+        file,
+        "  {module_name}_handle(uint32_t threads, int argc, const char** argv) : model({module_name}_context(&context, threads, argc, argv), \"TOP\") {{}}"
+    )?;
+    writeln!(file, "}};")?;
+    writeln!(file)?;
+    Ok(())
+}
+
+/// Releases before 4.200 keep the arguments and time in globals, the thread
+/// count is fixed when the model is built.
+fn write_global_handle(file: &mut std::fs::File, module_name: &str) -> err::Result<()> {
+    writeln!(
+        file,
+        "static const char* {module_name}_args(int argc, const char** argv) {{"
+    )?;
+    writeln!(file, "  Verilated::commandArgs(argc, argv);")?;
+    writeln!(file, "  return \"TOP\";")?;
+    writeln!(file, "}}")?;
+    writeln!(file)?;
+
+    writeln!(file, "struct {module_name}_handle {{")?;
+    writeln!(file, "  V{module_name} model;")?;
+    writeln!(
+        file,
+        "  {module_name}_handle(uint32_t threads, int argc, const char** argv) : model({module_name}_args(argc, argv)) {{}}"
+    )?;
+    writeln!(file, "}};")?;
+    writeln!(file)?;
+    Ok(())
+}
+
+fn write_save_fns(file: &mut std::fs::File, module_name: &str) -> err::Result<()> {
+    writeln!(
+        file,
+        "bool {module_name}_save({module_name}_handle* handle, const char* path, const uint64_t* words, size_t len) {{"
+    )?;
+    writeln!(file, "  VerilatedSave os;")?;
+    writeln!(file, "  os.open(path);")?;
+    writeln!(file, "  if (!os.isOpen()) return false;")?;
+    writeln!(file, "  uint64_t count = len;")?;
+    writeln!(file, "  os << count;")?;
+    writeln!(file, "  for (size_t i = 0; i < len; i++) {{")?;
+    writeln!(file, "    uint64_t word = words[i];")?;
+    writeln!(file, "    os << word;")?;
+    writeln!(file, "  }}")?;
+    writeln!(file, "  os << handle->model;")?;
+    writeln!(file, "  os.close();")?;
+    writeln!(file, "  return true;")?;
+    writeln!(file, "}}")?;
+
+    writeln!(
+        file,
+        "bool {module_name}_restore({module_name}_handle* handle, const char* path, uint64_t* words, size_t len) {{"
+    )?;
+    writeln!(file, "  VerilatedRestore os;")?;
+    writeln!(file, "  os.open(path);")?;
+    writeln!(file, "  if (!os.isOpen()) return false;")?;
+    writeln!(file, "  uint64_t count = 0;")?;
+    writeln!(file, "  os >> count;")?;
+    writeln!(file, "  if (count != len) return false;")?;
+    writeln!(file, "  for (size_t i = 0; i < len; i++) {{")?;
+    writeln!(file, "    os >> words[i];")?;
+    writeln!(file, "  }}")?;
+    writeln!(file, "  os >> handle->model;")?;
+    writeln!(file, "  os.close();")?;
+    writeln!(file, "  return true;")?;
+    writeln!(file, "}}")?;
+    Ok(())
+}
+
 fn c_type_name(data_type: DataType) -> &'static str {
     match data_type {
         DataType::Bool => "uint8_t",
@@ -0,0 +1,158 @@
+use crate::Value;
+use ferrilator_core::err;
+
+/// An `N` bit value where each bit is 0, 1 or unknown (X). Verilator only
+/// simulates two states, so unknown bits come from comparing runs, as in
+/// [`xcheck`](crate::xcheck), or from values a test marks as don't care.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Logic<const N: u32> {
+    bits: u128,
+    x_mask: u128,
+}
+
+impl<const N: u32> Logic<N> {
+    const MASK: u128 = {
+        assert!(N >= 1 && N <= 128, "Logic must have between 1 and 128 bits");
+        if N == 128 { u128::MAX } else { (1 << N) - 1 }
+    };
+
+    /// A value with every bit known. Bits above `N` are discarded.
+    pub fn new(bits: u128) -> Logic<N> {
+        Logic::with_x(bits, 0)
+    }
+
+    /// A value with the bits set in `x_mask` unknown.
+    pub fn with_x(bits: u128, x_mask: u128) -> Logic<N> {
+        let x_mask = x_mask & Self::MASK;
+        Logic {
+            bits: bits & Self::MASK & !x_mask,
+            x_mask,
+        }
+    }
+
+    /// A value with every bit unknown.
+    pub fn x() -> Logic<N> {
+        Logic::with_x(0, u128::MAX)
+    }
+
+    /// The known bits, unknown bits read as 0.
+    pub fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    /// The unknown bits.
+    pub fn x_mask(&self) -> u128 {
+        self.x_mask
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.x_mask == 0
+    }
+
+    /// The value if every bit is known.
+    pub fn known(&self) -> Option<u128> {
+        self.is_known().then_some(self.bits)
+    }
+
+    /// Bit `index`, or `None` if it is unknown.
+    pub fn bit(&self, index: u32) -> Option<bool> {
+        assert!(index < N, "bit {index} out of range for Logic<{N}>");
+        if self.x_mask >> index & 1 == 1 {
+            None
+        } else {
+            Some(self.bits >> index & 1 == 1)
+        }
+    }
+
+    /// Whether `bits` agrees with every known bit.
+    pub fn matches(&self, bits: u128) -> bool {
+        (bits & Self::MASK & !self.x_mask) == self.bits
+    }
+
+    /// The value seen by either of two runs: bits that differ, or are
+    /// unknown in either, are unknown.
+    pub fn merge(&self, other: Logic<N>) -> Logic<N> {
+        let x_mask = self.x_mask | other.x_mask | (self.bits ^ other.bits);
+        Logic::with_x(self.bits, x_mask)
+    }
+
+    /// Convert a port value of the same width.
+    pub fn from_value(value: Value) -> err::Result<Logic<N>> {
+        if value.width() != N {
+            return err::mismatch!("value of {} bits cannot be a Logic<{N}>", value.width());
+        }
+        Ok(Logic::new(value.bits()))
+    }
+}
+
+impl<const N: u32> std::fmt::Display for Logic<N> {
+    /// Formatted like a SystemVerilog literal, `4'b10x1`.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        fmt_logic(f, self.bits, self.x_mask, N)
+    }
+}
+
+/// Write `width` bits as a SystemVerilog binary literal, with `x` for the
+/// bits in `x_mask`.
+pub(crate) fn fmt_logic(
+    f: &mut std::fmt::Formatter,
+    bits: u128,
+    x_mask: u128,
+    width: u32,
+) -> std::fmt::Result {
+    write!(f, "{width}'b")?;
+    for index in (0..width).rev() {
+        let digit = if x_mask >> index & 1 == 1 {
+            'x'
+        } else if bits >> index & 1 == 1 {
+            '1'
+        } else {
+            '0'
+        };
+        write!(f, "{digit}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logic_bits() {
+        let value = Logic::<4>::with_x(0b1111, 0b0010);
+        assert_eq!(0b1101, value.bits());
+        assert_eq!(0b0010, value.x_mask());
+        assert_eq!(Some(true), value.bit(0));
+        assert_eq!(None, value.bit(1));
+        assert!(!value.is_known());
+        assert_eq!(None, value.known());
+        assert!(value.matches(0b1111));
+        assert!(value.matches(0b1101));
+        assert!(!value.matches(0b0101));
+        assert_eq!("4'b11x1", value.to_string());
+
+        assert_eq!(Some(5), Logic::<4>::new(0x15).known());
+        assert_eq!("3'bxxx", Logic::<3>::x().to_string());
+        assert!(Logic::<128>::x().matches(u128::MAX));
+    }
+
+    #[test]
+    fn logic_merge() {
+        let a = Logic::<8>::new(0b1010_0001);
+        let b = Logic::<8>::new(0b1010_0100);
+        let merged = a.merge(b);
+        assert_eq!("8'b10100x0x", merged.to_string());
+        assert_eq!(merged, merged.merge(a));
+        assert_eq!(a, a.merge(a));
+    }
+
+    #[test]
+    fn logic_from_value() {
+        assert_eq!(
+            Logic::<8>::new(0xab),
+            Logic::<8>::from_value(Value::from(0xabu8)).unwrap()
+        );
+        assert!(Logic::<4>::from_value(Value::from(0xabu8)).is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! A design for the unit tests, standing in for a generated binding.
+
+use crate::Clocked;
+use crate::Dut;
+use crate::PortInfo;
+use crate::Scheduled;
+use crate::Scheduler;
+use crate::Value;
+use ferrilator_core::err;
+
+/// The clock period of a [`Mock`] in picoseconds.
+pub(crate) const PERIOD: u64 = 10;
+
+/// Keeps the value of every port, runs `comb` on each `eval` and `seq` on
+/// each rising edge of the clock. The clock is driven by the [`Clocked`]
+/// methods, or by the [`Scheduled`] ones on the port named `clk`.
+pub(crate) struct Mock {
+    ports: &'static [PortInfo],
+    values: Vec<u128>,
+    cycle: u64,
+    time: u64,
+    clocks: Scheduler,
+    comb: fn(&mut Mock),
+    seq: fn(&mut Mock),
+}
+
+impl Mock {
+    /// Every port starts at zero, and nothing happens on `eval` or a clock
+    /// edge until [`Mock::on_eval`] or [`Mock::on_posedge`] is called.
+    pub(crate) fn new(ports: &'static [PortInfo]) -> Mock {
+        Mock {
+            ports,
+            values: vec![0; ports.len()],
+            cycle: 0,
+            time: 0,
+            clocks: Scheduler::new(&[(PERIOD, false)]),
+            comb: |_| {},
+            seq: |_| {},
+        }
+    }
+
+    pub(crate) fn on_eval(mut self, comb: fn(&mut Mock)) -> Mock {
+        self.comb = comb;
+        self
+    }
+
+    pub(crate) fn on_posedge(mut self, seq: fn(&mut Mock)) -> Mock {
+        self.seq = seq;
+        self
+    }
+
+    /// The value of a port of either direction.
+    pub(crate) fn get(&self, name: &str) -> u128 {
+        self.values[self.index(name)]
+    }
+
+    /// Set a port of either direction, truncating to its width.
+    pub(crate) fn set(&mut self, name: &str, bits: u128) {
+        let index = self.index(name);
+        self.values[index] = Value::new(bits, self.ports[index].width()).bits();
+    }
+
+    fn index(&self, name: &str) -> usize {
+        let index = self.ports.iter().position(|port| port.name() == name);
+        index.unwrap_or_else(|| panic!("no port named '{name}'"))
+    }
+
+    fn rising_edge(&mut self) {
+        self.cycle += 1;
+        (self.seq)(self);
+    }
+}
+
+/// A software model of the counter in ferrilator_test, adding one to
+/// `value` on each rising edge of `clk` while `enable` is set.
+pub(crate) fn counter() -> Mock {
+    const PORTS: [PortInfo; 3] = [
+        PortInfo::new("clk", 1, false, true, false),
+        PortInfo::new("enable", 1, false, true, false),
+        PortInfo::new("value", 8, false, false, true),
+    ];
+    Mock::new(&PORTS).on_posedge(|dut| {
+        if dut.get("enable") == 1 {
+            dut.set("value", dut.get("value") + 1);
+        }
+    })
+}
+
+impl Dut for Mock {
+    fn eval(&mut self) {
+        (self.comb)(self);
+    }
+
+    fn time(&self) -> u64 {
+        self.time
+    }
+
+    fn cycle(&self) -> u64 {
+        self.cycle
+    }
+
+    fn finish(&mut self) {}
+
+    fn ports(&self) -> &[PortInfo] {
+        self.ports
+    }
+
+    fn set_port(&mut self, name: &str, value: Value) -> err::Result<()> {
+        PortInfo::find(self.ports, name)?.check_write(value)?;
+        self.set(name, value.bits());
+        Ok(())
+    }
+
+    fn get_port(&self, name: &str) -> err::Result<Value> {
+        let port = PortInfo::find(self.ports, name)?;
+        Ok(Value::new(self.get(name), port.width()))
+    }
+}
+
+impl Clocked for Mock {
+    fn posedge(&mut self) {
+        self.time += PERIOD / 2;
+        self.rising_edge();
+        self.eval();
+    }
+
+    fn negedge(&mut self) {
+        self.time += PERIOD / 2;
+        self.eval();
+    }
+
+    fn tick(&mut self) {
+        self.posedge();
+        self.negedge();
+    }
+}
+
+impl Scheduled for Mock {
+    fn clocks(&self) -> &[&'static str] {
+        &["clk"]
+    }
+
+    fn step(&mut self) {
+        let (time, edges) = self.clocks.advance();
+        self.time = time;
+        for (_, level) in edges {
+            self.set("clk", level as u128);
+            if level {
+                self.rising_edge();
+            }
+        }
+        self.eval();
+    }
+
+    fn run_for(&mut self, ns: u64) {
+        let end = self.time + ns * 1000;
+        while self.clocks.next_edge().is_some_and(|next| next <= end) {
+            self.step();
+        }
+        self.time = end;
+    }
+}
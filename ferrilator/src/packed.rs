@@ -0,0 +1,46 @@
+use crate::PortType;
+
+/// A Rust type with the bit layout of a SystemVerilog packed struct, so it
+/// can be the type of a port. Derive it with `#[derive(ferrilator::Packed)]`
+/// on a struct with a `#[bits(n)]` attribute on every field. As in
+/// SystemVerilog, the first field takes the most significant bits.
+///
+/// ```ignore
+/// // typedef struct packed { logic [3:0] op; logic [11:0] addr; logic valid; } command_t;
+/// #[derive(Clone, Copy, Debug, PartialEq, ferrilator::Packed)]
+/// struct Command {
+///     #[bits(4)]
+///     op: u8,
+///     #[bits(12)]
+///     addr: u16,
+///     #[bits(1)]
+///     valid: bool,
+/// }
+/// ```
+///
+/// Fields can be `bool`, integers, which are sign extended when signed, or
+/// other `Packed` types of the same width.
+pub trait Packed: Copy + PartialEq {
+    /// The width in bits, at most 128.
+    const WIDTH: u32;
+
+    /// The value as bits, the low `WIDTH` bits are used.
+    fn pack(&self) -> u128;
+
+    /// The value held in the low `WIDTH` bits of `bits`, the rest are
+    /// ignored.
+    fn unpack(bits: u128) -> Self;
+}
+
+impl<T: Packed> PortType for T {
+    const WIDTH: u32 = T::WIDTH;
+    type Read = T;
+
+    fn to_bits(&self) -> u128 {
+        self.pack()
+    }
+
+    fn from_bits(bits: u128) -> T {
+        T::unpack(bits)
+    }
+}
@@ -0,0 +1,351 @@
+use ferrilator_core::err;
+
+/// Describes one port of a generated binding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PortInfo {
+    name: &'static str,
+    width: u32,
+    signed: bool,
+    input: bool,
+    output: bool,
+}
+
+impl PortInfo {
+    #[doc(hidden)]
+    pub const fn new(
+        name: &'static str,
+        width: u32,
+        signed: bool,
+        input: bool,
+        output: bool,
+    ) -> Self {
+        PortInfo {
+            name,
+            width,
+            signed,
+            input,
+            output,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Width of the port in bits.
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn signed(&self) -> bool {
+        self.signed
+    }
+
+    pub fn input(&self) -> bool {
+        self.input
+    }
+
+    pub fn output(&self) -> bool {
+        self.output
+    }
+
+    /// Used by generated bindings to check the width of a port declared
+    /// with a [`PortType`], against the width `ferrilator::build` read from
+    /// the Verilated model, if it was given.
+    #[doc(hidden)]
+    pub const fn width_matches(width: u32, verilated: Option<&str>) -> bool {
+        let Some(verilated) = verilated else {
+            return true;
+        };
+        let digits = verilated.as_bytes();
+        let mut parsed = 0;
+        let mut index = 0;
+        while index < digits.len() {
+            parsed = parsed * 10 + (digits[index] - b'0') as u32;
+            index += 1;
+        }
+        parsed == width
+    }
+
+    /// Find a port by name.
+    pub fn find<'a>(ports: &'a [PortInfo], name: &str) -> err::Result<&'a PortInfo> {
+        match ports.iter().find(|port| port.name == name) {
+            Some(port) => Ok(port),
+            None => err::input!("no port named '{name}'"),
+        }
+    }
+
+    /// Fail unless `value` can be driven onto this port.
+    pub fn check_write(&self, value: Value) -> err::Result<()> {
+        let name = self.name;
+        if !self.input {
+            return err::input!("port '{name}' is not an input");
+        }
+        if self.width < 128 && value.bits >> self.width != 0 {
+            return err::input!(
+                "value {value} does not fit in {} bits of '{name}'",
+                self.width
+            );
+        }
+        Ok(())
+    }
+
+    /// Parse text such as `true`, `42`, `-3`, `0x2a`, `0b101` or `0o52` as a
+    /// value for this port. Underscores are ignored and negative numbers are
+    /// only accepted for signed ports.
+    pub fn parse_value(&self, text: &str) -> err::Result<Value> {
+        let name = self.name;
+        let clean = text.trim().replace('_', "");
+        let (negative, digits) = match clean.strip_prefix('-') {
+            Some(digits) => (true, digits),
+            None => (false, clean.as_str()),
+        };
+        let parsed = match digits {
+            "true" if !negative => Ok(1),
+            "false" if !negative => Ok(0),
+            _ => {
+                if let Some(hex) = digits.strip_prefix("0x") {
+                    u128::from_str_radix(hex, 16)
+                } else if let Some(bin) = digits.strip_prefix("0b") {
+                    u128::from_str_radix(bin, 2)
+                } else if let Some(oct) = digits.strip_prefix("0o") {
+                    u128::from_str_radix(oct, 8)
+                } else {
+                    digits.parse()
+                }
+            }
+        };
+        let magnitude = match parsed {
+            Ok(magnitude) => magnitude,
+            Err(_) => return err::input!("cannot parse '{text}' as a value for '{name}'"),
+        };
+
+        if negative {
+            if !self.signed {
+                return err::input!("port '{name}' is unsigned, cannot take '{text}'");
+            }
+            if self.width < 128 && magnitude > 1 << (self.width - 1) {
+                return err::input!(
+                    "value {text} does not fit in {} bits of '{name}'",
+                    self.width
+                );
+            }
+            return Ok(Value::new(magnitude.wrapping_neg(), self.width));
+        }
+
+        if self.width < 128 && magnitude >> self.width != 0 {
+            return err::input!(
+                "value {text} does not fit in {} bits of '{name}'",
+                self.width
+            );
+        }
+        Ok(Value::new(magnitude, self.width))
+    }
+}
+
+/// A Rust type that ports can be declared with, other than `bool` and the
+/// integers. It is implemented for every [`Packed`](crate::Packed) struct
+/// and derived for enums with `#[derive(ferrilator::HwEnum)]`.
+pub trait PortType: Copy {
+    /// The width in bits, at most 128.
+    const WIDTH: u32;
+
+    /// The type returned by getters, `Self` unless some bit patterns have no
+    /// Rust value.
+    type Read: Copy + PartialEq;
+
+    /// The value as bits, the low `WIDTH` bits are used.
+    fn to_bits(&self) -> u128;
+
+    /// The value held in the low `WIDTH` bits of `bits`.
+    fn from_bits(bits: u128) -> Self::Read;
+}
+
+/// The value of a port, as an unsigned bit pattern of a given width.
+/// Signed values are held in two's complement.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Value {
+    bits: u128,
+    width: u32,
+}
+
+impl Value {
+    /// Bits above `width` are discarded.
+    pub fn new(bits: u128, width: u32) -> Value {
+        let bits = if width < 128 {
+            bits & ((1 << width) - 1)
+        } else {
+            bits
+        };
+        Value { bits, width }
+    }
+
+    pub fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.width == 1 {
+            write!(f, "{}", self.bits)
+        } else {
+            write!(f, "{:#x}", self.bits)
+        }
+    }
+}
+
+impl From<bool> for Value {
+    fn from(v: bool) -> Value {
+        Value::new(v as u128, 1)
+    }
+}
+
+macro_rules! value_from {
+    ($ty:ty, $unsigned:ty) => {
+        impl From<$ty> for Value {
+            fn from(v: $ty) -> Value {
+                Value::new(v as $unsigned as u128, <$ty>::BITS)
+            }
+        }
+    };
+}
+
+value_from!(u8, u8);
+value_from!(u16, u16);
+value_from!(u32, u32);
+value_from!(u64, u64);
+value_from!(u128, u128);
+value_from!(i8, u8);
+value_from!(i16, u16);
+value_from!(i32, u32);
+value_from!(i64, u64);
+
+/// The widths of the ports declared in the `V{module}.h` header Verilator
+/// writes, from lines like `VL_IN8(&clk,0,0);` or `VL_OUTW(&wide,99,0,4);`.
+pub(crate) fn verilated_widths(header: &str) -> Vec<(String, u32)> {
+    let mut widths = vec![];
+    for line in header.lines() {
+        let line = line.trim();
+        let Some(rest) = ["VL_IN", "VL_OUT", "VL_INOUT"]
+            .iter()
+            .find_map(|prefix| line.strip_prefix(prefix))
+        else {
+            continue;
+        };
+        let Some((_, args)) = rest.split_once('(') else {
+            continue;
+        };
+        let Some((args, _)) = args.split_once(')') else {
+            continue;
+        };
+        let args: Vec<_> = args.split(',').map(str::trim).collect();
+        // unpacked arrays are declared as `&name[4]`
+        let name = args[0].trim_start_matches('&');
+        let name = name.split_once('[').map_or(name, |(name, _)| name);
+        if let [_, msb, lsb, ..] = args[..]
+            && let (Ok(msb), Ok(lsb)) = (msb.parse::<u32>(), lsb.parse::<u32>())
+        {
+            widths.push((name.to_string(), msb.abs_diff(lsb) + 1));
+        }
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_from() {
+        assert_eq!(Value::new(1, 1), Value::from(true));
+        assert_eq!(Value::new(0xab, 8), Value::from(0xabu8));
+        assert_eq!(Value::new(0xff, 8), Value::from(-1i8));
+        assert_eq!(Value::new(0xffff_fffe, 32), Value::from(-2i32));
+        assert_eq!(Value::new(u128::MAX, 128), Value::from(u128::MAX));
+        assert_eq!(Value::new(0x34, 8), Value::new(0x1234, 8));
+    }
+
+    #[test]
+    fn port_verilated_widths() {
+        let header = "
+    // PORTS
+    VL_IN8(&clk,0,0);
+    VL_OUT8(&state,2,0);
+    VL_IN16(reversed,0,11);
+    VL_INW(&wide,99,0,4);
+    VL_OUT(&lanes[4],31,0);
+    VL_OUT64(&count,63,0);
+
+    // INTERNAL VARIABLES
+    CData/*0:0*/ counter__DOT__enable;
+";
+        assert_eq!(
+            vec![
+                ("clk".to_string(), 1),
+                ("state".to_string(), 3),
+                ("reversed".to_string(), 12),
+                ("wide".to_string(), 100),
+                ("lanes".to_string(), 32),
+                ("count".to_string(), 64),
+            ],
+            verilated_widths(header)
+        );
+
+        assert!(PortInfo::width_matches(3, None));
+        assert!(PortInfo::width_matches(3, Some("3")));
+        assert!(PortInfo::width_matches(100, Some("100")));
+        assert!(!PortInfo::width_matches(3, Some("4")));
+    }
+
+    #[test]
+    fn port_check_write() {
+        let ports = [
+            PortInfo::new("a", 4, false, true, false),
+            PortInfo::new("b", 8, false, false, true),
+        ];
+
+        let a = PortInfo::find(&ports, "a").unwrap();
+        assert_eq!(Ok(()), a.check_write(Value::from(15u32)));
+        assert!(a.check_write(Value::from(16u32)).is_err());
+
+        let b = PortInfo::find(&ports, "b").unwrap();
+        assert!(b.check_write(Value::from(0u8)).is_err());
+
+        assert!(PortInfo::find(&ports, "c").is_err());
+    }
+
+    #[test]
+    fn port_parse_value() -> err::Result<()> {
+        let flag = PortInfo::new("flag", 1, false, true, false);
+        assert_eq!(Value::from(true), flag.parse_value("true")?);
+        assert_eq!(Value::from(false), flag.parse_value("0")?);
+        assert!(flag.parse_value("2").is_err());
+
+        let byte = PortInfo::new("byte", 8, false, true, false);
+        assert_eq!(Value::from(42u8), byte.parse_value("42")?);
+        assert_eq!(Value::from(42u8), byte.parse_value("0x2a")?);
+        assert_eq!(Value::from(5u8), byte.parse_value("0b101")?);
+        assert_eq!(Value::from(42u8), byte.parse_value("0o52")?);
+        assert_eq!(Value::from(255u8), byte.parse_value("0b1111_1111")?);
+        assert!(byte.parse_value("256").is_err());
+        assert!(byte.parse_value("-1").is_err());
+        assert!(byte.parse_value("x").is_err());
+
+        let signed = PortInfo::new("signed", 8, true, true, false);
+        assert_eq!(Value::from(-3i8), signed.parse_value("-3")?);
+        assert_eq!(Value::from(-128i8), signed.parse_value("-128")?);
+        assert!(signed.parse_value("-129").is_err());
+
+        let wide = PortInfo::new("wide", 128, false, true, false);
+        assert_eq!(
+            Value::from(u128::MAX),
+            wide.parse_value(&u128::MAX.to_string())?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! Checkpoints of designs built with the `savable` option, so tests can
+//! start from a saved state instead of repeating a long setup.
+
+use ferrilator_core::err;
+use std::ffi::CString;
+use std::ffi::c_char;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+
+/// The saved state of a design, held in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Snapshot {
+    bytes: Vec<u8>,
+}
+
+impl Snapshot {
+    pub fn from_bytes(bytes: Vec<u8>) -> Snapshot {
+        Snapshot { bytes }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Call `save` with a temporary path and read back what it wrote.
+    pub fn capture(save: impl FnOnce(&Path) -> err::Result<()>) -> err::Result<Snapshot> {
+        let path = TempPath::new();
+        save(&path.0)?;
+        Ok(Snapshot {
+            bytes: std::fs::read(&path.0)?,
+        })
+    }
+
+    /// Write the snapshot to a temporary path and call `restore` with it.
+    pub fn apply(&self, restore: impl FnOnce(&Path) -> err::Result<()>) -> err::Result<()> {
+        let path = TempPath::new();
+        std::fs::write(&path.0, &self.bytes)?;
+        restore(&path.0)
+    }
+}
+
+/// A file in the temporary directory, removed on drop.
+struct TempPath(PathBuf);
+
+impl TempPath {
+    fn new() -> TempPath {
+        static NEXT: AtomicUsize = AtomicUsize::new(0);
+        let name = format!(
+            "ferrilator-{}-{}.save",
+            std::process::id(),
+            NEXT.fetch_add(1, Ordering::Relaxed)
+        );
+        TempPath(std::env::temp_dir().join(name))
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+pub(crate) fn c_path(path: &Path) -> err::Result<CString> {
+    match CString::new(path.as_os_str().as_encoded_bytes()) {
+        Ok(path) => Ok(path),
+        Err(_) => err::input!("path {} contains a nul byte", path.display()),
+    }
+}
+
+/// Used by generated bindings to call the C++ save function.
+#[doc(hidden)]
+pub fn write(
+    path: &Path,
+    words: &[u64],
+    save: impl FnOnce(*const c_char, *const u64, usize) -> bool,
+) -> err::Result<()> {
+    let c_path = c_path(path)?;
+    if !save(c_path.as_ptr(), words.as_ptr(), words.len()) {
+        return err::io!("cannot save to {}", path.display());
+    }
+    Ok(())
+}
+
+/// Used by generated bindings to call the C++ restore function.
+#[doc(hidden)]
+pub fn read(
+    path: &Path,
+    words: &mut [u64],
+    restore: impl FnOnce(*const c_char, *mut u64, usize) -> bool,
+) -> err::Result<()> {
+    let c_path = c_path(path)?;
+    if !restore(c_path.as_ptr(), words.as_mut_ptr(), words.len()) {
+        return err::io!("cannot restore from {}", path.display());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_round_trip() -> err::Result<()> {
+        let snapshot = Snapshot::capture(|path| {
+            std::fs::write(path, b"state")?;
+            Ok(())
+        })?;
+        assert_eq!(b"state", snapshot.bytes());
+
+        let mut seen = vec![];
+        let mut used = PathBuf::new();
+        snapshot.apply(|path| {
+            seen = std::fs::read(path)?;
+            used = path.to_path_buf();
+            Ok(())
+        })?;
+        assert_eq!(b"state".to_vec(), seen);
+        assert!(!used.exists());
+        Ok(())
+    }
+
+    #[test]
+    fn save_failures() {
+        assert!(Snapshot::capture(|_| err::io!("disk full")).is_err());
+        assert!(write(Path::new("a\0b"), &[], |_, _, _| true).is_err());
+        assert!(write(Path::new("state"), &[], |_, _, _| false).is_err());
+        assert!(read(Path::new("state"), &mut [0], |_, _, _| false).is_err());
+    }
+}
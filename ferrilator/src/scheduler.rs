@@ -0,0 +1,195 @@
+/// Tracks the level and next edge of each clock of a design, so simulation
+/// time can be advanced from one edge to the next. Generated bindings hold
+/// one of these, it is rarely needed directly.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Scheduler {
+    clocks: Vec<ClockState>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct ClockState {
+    period_ps: u64,
+    level: bool,
+    next_ps: u64,
+    /// The level after an active edge, the opposite of the initial level.
+    active: bool,
+    cycles: u64,
+}
+
+impl ClockState {
+    /// How long the clock stays at `level`. The high phase is the shorter
+    /// for odd periods.
+    fn duration(&self, level: bool) -> u64 {
+        let high = self.period_ps / 2;
+        if level { high } else { self.period_ps - high }
+    }
+
+    fn set_level(&mut self, level: bool) {
+        if level != self.level && level == self.active {
+            self.cycles += 1;
+        }
+        self.level = level;
+    }
+}
+
+impl Scheduler {
+    /// One entry per clock giving its period and initial level. Each clock
+    /// has its first edge once it has spent its initial level's share of
+    /// the period. The edge away from the initial level is the active one.
+    pub fn new(clocks: &[(u64, bool)]) -> Scheduler {
+        let clocks = clocks
+            .iter()
+            .map(|&(period_ps, level)| {
+                let mut clock = ClockState {
+                    period_ps,
+                    level,
+                    next_ps: 0,
+                    active: !level,
+                    cycles: 0,
+                };
+                clock.next_ps = clock.duration(level);
+                clock
+            })
+            .collect();
+        Scheduler { clocks }
+    }
+
+    pub fn level(&self, index: usize) -> bool {
+        self.clocks[index].level
+    }
+
+    /// The number of active edges of a clock so far.
+    pub fn cycles(&self, index: usize) -> u64 {
+        self.clocks[index].cycles
+    }
+
+    /// Time of the next edge of any clock.
+    pub fn next_edge(&self) -> Option<u64> {
+        self.clocks.iter().map(|clock| clock.next_ps).min()
+    }
+
+    /// Move to the next edge, toggling every clock that has an edge at that
+    /// time. Returns the time and the index and new level of each clock that
+    /// changed.
+    pub fn advance(&mut self) -> (u64, Vec<(usize, bool)>) {
+        let Some(now) = self.next_edge() else {
+            return (0, vec![]);
+        };
+        let mut edges = vec![];
+        for (index, clock) in self.clocks.iter_mut().enumerate() {
+            if clock.next_ps == now {
+                clock.set_level(!clock.level);
+                clock.next_ps = now + clock.duration(clock.level);
+                edges.push((index, clock.level));
+            }
+        }
+        (now, edges)
+    }
+
+    /// Record an edge driven directly, such as by `posedge()`, so the next
+    /// scheduled edge of that clock follows on from it.
+    pub fn drive(&mut self, index: usize, level: bool, now: u64) {
+        let clock = &mut self.clocks[index];
+        clock.set_level(level);
+        clock.next_ps = now + clock.duration(level);
+    }
+
+    /// The level, next edge and cycle count of each clock, three words per
+    /// clock.
+    pub fn save(&self) -> Vec<u64> {
+        self.clocks
+            .iter()
+            .flat_map(|clock| [clock.level as u64, clock.next_ps, clock.cycles])
+            .collect()
+    }
+
+    /// Return to a state from `save`.
+    pub fn restore(&mut self, words: &[u64]) {
+        for (clock, saved) in self.clocks.iter_mut().zip(words.chunks(3)) {
+            clock.level = saved[0] != 0;
+            clock.next_ps = saved[1];
+            clock.cycles = saved[2];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scheduler_edges() {
+        let mut sched = Scheduler::new(&[(10, false), (25, false), (4, true)]);
+
+        let mut seen = vec![];
+        while let Some(next) = sched.next_edge()
+            && next <= 20
+        {
+            seen.push(sched.advance());
+        }
+
+        assert_eq!(
+            vec![
+                (2, vec![(2, false)]),
+                (4, vec![(2, true)]),
+                (5, vec![(0, true)]),
+                (6, vec![(2, false)]),
+                (8, vec![(2, true)]),
+                (10, vec![(0, false), (2, false)]),
+                (12, vec![(2, true)]),
+                (13, vec![(1, true)]),
+                (14, vec![(2, false)]),
+                (15, vec![(0, true)]),
+                (16, vec![(2, true)]),
+                (18, vec![(2, false)]),
+                (20, vec![(0, false), (2, true)]),
+            ],
+            seen
+        );
+    }
+
+    #[test]
+    fn scheduler_odd_period() {
+        let mut sched = Scheduler::new(&[(5, false)]);
+        assert_eq!((3, vec![(0, true)]), sched.advance());
+        assert_eq!((5, vec![(0, false)]), sched.advance());
+        assert_eq!((8, vec![(0, true)]), sched.advance());
+    }
+
+    #[test]
+    fn scheduler_drive() {
+        let mut sched = Scheduler::new(&[(10, false)]);
+        sched.drive(0, true, 7);
+        assert!(sched.level(0));
+        assert_eq!(Some(12), sched.next_edge());
+        assert_eq!((12, vec![(0, false)]), sched.advance());
+    }
+
+    #[test]
+    fn scheduler_cycles() {
+        let mut sched = Scheduler::new(&[(10, false), (4, true)]);
+        while sched.next_edge().is_some_and(|next| next <= 20) {
+            sched.advance();
+        }
+        assert_eq!(2, sched.cycles(0));
+        assert_eq!(5, sched.cycles(1));
+
+        // driving the level a clock already has is not an edge
+        sched.drive(0, false, 20);
+        sched.drive(0, true, 25);
+        sched.drive(0, true, 25);
+        assert_eq!(3, sched.cycles(0));
+    }
+
+    #[test]
+    fn scheduler_save() {
+        let mut sched = Scheduler::new(&[(10, false), (4, true)]);
+        sched.advance();
+        let saved = sched.save();
+        assert_eq!(vec![0, 5, 0, 0, 4, 1], saved);
+
+        let mut other = Scheduler::new(&[(10, false), (4, true)]);
+        other.restore(&saved);
+        assert_eq!(sched, other);
+    }
+}
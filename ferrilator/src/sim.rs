@@ -0,0 +1,367 @@
+//! An executor for writing testbenches as concurrent tasks, in the style of
+//! cocotb. Tasks share the design through a [`Sim`] handle and wait for
+//! clock edges, cycles or changes on a port; the executor steps the design
+//! from one clock edge to the next whenever every task is waiting.
+//!
+//! ```ignore
+//! let mut dut = counter();
+//! ferrilator::sim::run(&mut dut, |sim| async move {
+//!     let monitor = sim.clone();
+//!     sim.spawn(async move {
+//!         loop {
+//!             monitor.signal_change("value").await;
+//!             println!("value is now {}", monitor.get("value"));
+//!         }
+//!     });
+//!     sim.set("enable", true);
+//!     sim.cycles(5).await;
+//! });
+//! ```
+
+use crate::Scheduled;
+use crate::Value;
+use crate::port::PortInfo;
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::task::Context;
+use std::task::Poll;
+use std::task::Wake;
+use std::task::Waker;
+
+type Task<'a> = Pin<Box<dyn Future<Output = ()> + 'a>>;
+
+/// A handle on the running simulation, cheap to clone into each task.
+pub struct Sim<'a, D> {
+    inner: Rc<Inner<'a, D>>,
+}
+
+struct Inner<'a, D> {
+    dut: RefCell<&'a mut D>,
+    spawned: RefCell<Vec<Task<'a>>>,
+    waiters: RefCell<Vec<Waiter>>,
+}
+
+/// A task waiting for something to happen on a port.
+struct Waiter {
+    port: &'static str,
+    kind: WaitKind,
+    last: Value,
+    fired: Rc<Cell<bool>>,
+    waker: Waker,
+}
+
+#[derive(Copy, Clone, Debug)]
+enum WaitKind {
+    Edge(bool),
+    Change,
+    Cycles(usize),
+}
+
+impl<D> Clone for Sim<'_, D> {
+    fn clone(&self) -> Self {
+        Sim {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+/// Run `main` and every task it spawns until `main` completes, stepping the
+/// design between clock edges while all tasks are waiting. Tasks still
+/// running when `main` completes are dropped.
+///
+/// Panics if every task is waiting on something other than the design.
+pub fn run<'a, D, F, Fut>(dut: &'a mut D, main: F) -> Fut::Output
+where
+    D: Scheduled,
+    F: FnOnce(Sim<'a, D>) -> Fut,
+    Fut: Future + 'a,
+{
+    // settle the initial values so the first edge is seen as one
+    dut.eval();
+    let sim = Sim {
+        inner: Rc::new(Inner {
+            dut: RefCell::new(dut),
+            spawned: RefCell::new(vec![]),
+            waiters: RefCell::new(vec![]),
+        }),
+    };
+
+    let mut main = Box::pin(main(sim.clone()));
+    let main_flag = Flag::new();
+    let mut tasks: Vec<(Task<'a>, Arc<Flag>)> = vec![];
+    loop {
+        let mut progress = false;
+        if main_flag.take() {
+            progress = true;
+            let waker = Waker::from(main_flag.clone());
+            if let Poll::Ready(output) = main.as_mut().poll(&mut Context::from_waker(&waker)) {
+                return output;
+            }
+        }
+
+        tasks.extend(
+            sim.inner
+                .spawned
+                .borrow_mut()
+                .drain(..)
+                .map(|task| (task, Flag::new())),
+        );
+        tasks.retain_mut(|(task, flag)| {
+            if !flag.take() {
+                return true;
+            }
+            progress = true;
+            let waker = Waker::from(flag.clone());
+            task.as_mut()
+                .poll(&mut Context::from_waker(&waker))
+                .is_pending()
+        });
+        if progress || !sim.inner.spawned.borrow().is_empty() {
+            continue;
+        }
+
+        if sim.inner.waiters.borrow().is_empty() {
+            panic!("every task is waiting but none on the design");
+        }
+        // inputs driven by the tasks may change outputs before the next edge
+        sim.with(|dut| dut.eval());
+        if sim.wake() {
+            continue;
+        }
+        sim.with(|dut| dut.step());
+        sim.wake();
+    }
+}
+
+impl<'a, D: Scheduled> Sim<'a, D> {
+    /// Run `task` alongside the others.
+    pub fn spawn(&self, task: impl Future<Output = ()> + 'a) {
+        self.inner.spawned.borrow_mut().push(Box::pin(task));
+    }
+
+    /// Use the design directly, for instance to call its getters and
+    /// setters.
+    pub fn with<R>(&self, f: impl FnOnce(&mut D) -> R) -> R {
+        f(&mut self.inner.dut.borrow_mut())
+    }
+
+    /// The simulation time in picoseconds.
+    pub fn time(&self) -> u64 {
+        self.with(|dut| dut.time())
+    }
+
+    /// Drive an input port by name, panicking if it is not an input or the
+    /// value does not fit.
+    pub fn set(&self, port: &str, value: impl Into<Value>) {
+        let value = value.into();
+        if let Err(e) = self.with(|dut| dut.set_port(port, value)) {
+            panic!("{e}");
+        }
+    }
+
+    /// Read a port by name, panicking if there is no such port.
+    pub fn get(&self, port: &str) -> Value {
+        match self.with(|dut| dut.get_port(port)) {
+            Ok(value) => value,
+            Err(e) => panic!("{e}"),
+        }
+    }
+
+    /// Wait until `port` goes from low to high.
+    pub fn rising_edge(&self, port: &str) -> Trigger<'a, D> {
+        self.trigger(port, WaitKind::Edge(true))
+    }
+
+    /// Wait until `port` goes from high to low.
+    pub fn falling_edge(&self, port: &str) -> Trigger<'a, D> {
+        self.trigger(port, WaitKind::Edge(false))
+    }
+
+    /// Wait until `port` changes value.
+    pub fn signal_change(&self, port: &str) -> Trigger<'a, D> {
+        self.trigger(port, WaitKind::Change)
+    }
+
+    /// Wait for `n` rising edges of the first clock of the design.
+    pub fn cycles(&self, n: usize) -> Trigger<'a, D> {
+        let clock = self.with(|dut| dut.clocks()[0]);
+        self.trigger(clock, WaitKind::Cycles(n))
+    }
+
+    fn trigger(&self, port: &str, kind: WaitKind) -> Trigger<'a, D> {
+        let port = self.with(|dut| match PortInfo::find(dut.ports(), port) {
+            Ok(info) => info.name(),
+            Err(e) => panic!("{e}"),
+        });
+        Trigger {
+            sim: self.clone(),
+            port,
+            kind,
+            fired: None,
+        }
+    }
+
+    /// Compare every watched port with its last value, waking the tasks
+    /// whose condition now holds. Returns whether any were woken.
+    fn wake(&self) -> bool {
+        let mut woken = false;
+        let mut waiters = self.inner.waiters.borrow_mut();
+        waiters.retain_mut(|waiter| {
+            let now = self.get(waiter.port);
+            let before = std::mem::replace(&mut waiter.last, now);
+            let rose = before.bits() & 1 == 0 && now.bits() & 1 == 1;
+            let done = match &mut waiter.kind {
+                WaitKind::Edge(level) => before != now && (now.bits() & 1 == 1) == *level,
+                WaitKind::Change => before != now,
+                WaitKind::Cycles(n) => {
+                    if rose {
+                        *n -= 1;
+                    }
+                    *n == 0
+                }
+            };
+            if done {
+                waiter.fired.set(true);
+                waiter.waker.wake_by_ref();
+                woken = true;
+            }
+            !done
+        });
+        woken
+    }
+}
+
+/// Completes when the awaited event happens, returned by the waiting
+/// methods of [`Sim`].
+pub struct Trigger<'a, D> {
+    sim: Sim<'a, D>,
+    port: &'static str,
+    kind: WaitKind,
+    fired: Option<Rc<Cell<bool>>>,
+}
+
+impl<D: Scheduled> Future for Trigger<'_, D> {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(fired) = &self.fired {
+            return if fired.get() {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            };
+        }
+        if let WaitKind::Cycles(0) = self.kind {
+            return Poll::Ready(());
+        }
+        let fired = Rc::new(Cell::new(false));
+        let waiter = Waiter {
+            port: self.port,
+            kind: self.kind,
+            last: self.sim.get(self.port),
+            fired: fired.clone(),
+            waker: cx.waker().clone(),
+        };
+        self.sim.inner.waiters.borrow_mut().push(waiter);
+        self.fired = Some(fired);
+        Poll::Pending
+    }
+}
+
+/// Marks a task as ready to be polled.
+struct Flag(AtomicBool);
+
+impl Flag {
+    fn new() -> Arc<Flag> {
+        Arc::new(Flag(AtomicBool::new(true)))
+    }
+
+    fn take(&self) -> bool {
+        self.0.swap(false, Ordering::Relaxed)
+    }
+}
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dut;
+    use crate::mock::counter;
+
+    #[test]
+    fn sim_edges_and_cycles() {
+        let mut dut = counter();
+        let times = run(&mut dut, |sim| async move {
+            let mut times = vec![];
+            sim.rising_edge("clk").await;
+            times.push(sim.time());
+            sim.falling_edge("clk").await;
+            times.push(sim.time());
+            sim.cycles(3).await;
+            times.push(sim.time());
+            sim.cycles(0).await;
+            times.push(sim.time());
+            times
+        });
+        assert_eq!(vec![5, 10, 35, 35], times);
+    }
+
+    #[test]
+    fn sim_tasks() {
+        let mut dut = counter();
+        let changes = run(&mut dut, |sim| async move {
+            let changes = Rc::new(RefCell::new(vec![]));
+            let monitor = sim.clone();
+            let seen = changes.clone();
+            sim.spawn(async move {
+                loop {
+                    monitor.signal_change("value").await;
+                    let value = monitor.get("value").bits();
+                    seen.borrow_mut().push((monitor.time(), value));
+                }
+            });
+
+            let driver = sim.clone();
+            sim.spawn(async move {
+                driver.cycles(2).await;
+                driver.set("enable", true);
+                driver.cycles(3).await;
+                driver.set("enable", false);
+            });
+
+            sim.cycles(8).await;
+            changes.take()
+        });
+        assert_eq!(vec![(25, 1), (35, 2), (45, 3)], changes);
+        assert_eq!(3, dut.get("value"));
+    }
+
+    #[test]
+    fn sim_run_for() {
+        let mut dut = counter();
+        dut.set("enable", 1);
+        dut.run_for(1);
+        assert_eq!(1000, dut.time());
+        assert_eq!(100, dut.get("value"));
+    }
+
+    #[test]
+    #[should_panic(expected = "no port named 'nope'")]
+    fn sim_unknown_port() {
+        let mut dut = counter();
+        run(&mut dut, |sim| async move {
+            sim.rising_edge("nope").await;
+        });
+    }
+}
@@ -0,0 +1,419 @@
+//! Replay tables of test vectors against a design.
+//!
+//! Each row is one clock cycle and each column is named after a port. For
+//! every row the input columns are driven, the clock is ticked and the
+//! output columns are compared. A cell of `x` (or an empty cell) is a
+//! don't-care, for inputs that leaves the previous value in place.
+//!
+//! Tables can be written as CSV, with a header row of port names:
+//!
+//! ```text
+//! enable, reset, value, overflow
+//! 1,      0,     x,     x
+//! 1,      0,     0,     0
+//! ```
+//!
+//! or as JSON lines, one flat object per row:
+//!
+//! ```text
+//! {"enable": 1, "reset": 0}
+//! {"enable": 1, "reset": 0, "value": "0x00", "overflow": false}
+//! ```
+
+use crate::Clocked;
+use crate::PortInfo;
+use crate::Value;
+use ferrilator_core::err;
+use std::io::Read;
+
+/// A table of test vectors, cells are parsed against the ports of the
+/// design when the table is run.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Vectors {
+    columns: Vec<String>,
+    rows: Vec<Vec<Option<String>>>,
+}
+
+impl Vectors {
+    /// Load a table, `.csv` files are parsed as CSV and `.json`, `.jsonl`
+    /// or `.ndjson` files as JSON lines.
+    pub fn load(path: &str) -> err::Result<Vectors> {
+        let mut text = String::new();
+        std::fs::File::open(path)?.read_to_string(&mut text)?;
+        match path.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("csv") => Vectors::parse_csv(&text),
+            Some("json" | "jsonl" | "ndjson") => Vectors::parse_json_lines(&text),
+            _ => err::input!("cannot tell the format of {path} from its extension"),
+        }
+    }
+
+    /// Parse CSV with a header row. Blank lines and lines starting with `#`
+    /// are skipped, quoting is not supported.
+    pub fn parse_csv(text: &str) -> err::Result<Vectors> {
+        let mut lines = text
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'));
+
+        let columns: Vec<String> = match lines.next() {
+            Some((_, header)) => header.split(',').map(|s| s.trim().to_string()).collect(),
+            None => return err::input!("no header row"),
+        };
+
+        let mut rows = vec![];
+        for (index, line) in lines {
+            let cells: Vec<_> = line.split(',').map(cell).collect();
+            if cells.len() != columns.len() {
+                return err::input!(
+                    "line {} has {} cells, expected {}",
+                    index + 1,
+                    cells.len(),
+                    columns.len()
+                );
+            }
+            rows.push(cells);
+        }
+
+        Ok(Vectors { columns, rows })
+    }
+
+    /// Parse JSON lines, one flat object per row. Values may be numbers,
+    /// booleans, strings or `null`, keys missing from a row are don't-cares.
+    pub fn parse_json_lines(text: &str) -> err::Result<Vectors> {
+        let mut columns: Vec<String> = vec![];
+        let mut objects = vec![];
+        for (index, line) in text.lines().enumerate() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let object = match json::parse_object(line) {
+                Ok(object) => object,
+                Err(msg) => return err::input!("line {}: {msg}", index + 1),
+            };
+            for (key, _) in &object {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+            objects.push(object);
+        }
+
+        let rows = objects
+            .into_iter()
+            .map(|object| {
+                columns
+                    .iter()
+                    .map(|column| {
+                        object
+                            .iter()
+                            .find(|(key, _)| key == column)
+                            .and_then(|(_, value)| value.as_deref().and_then(cell))
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Ok(Vectors { columns, rows })
+    }
+
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Number of rows, which is the number of cycles the table runs for.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Run every row against `dut`, stopping at the first cycle where an
+    /// output differs from the table. The error for a mismatch lists the
+    /// expected and actual value of every port at that cycle.
+    pub fn run<D: Clocked>(&self, dut: &mut D) -> err::Result<()> {
+        let ports = dut.ports().to_vec();
+        let mut columns = vec![];
+        for column in &self.columns {
+            columns.push(*PortInfo::find(&ports, column)?);
+        }
+
+        for (cycle, row) in self.rows.iter().enumerate() {
+            let mut expected = vec![];
+            for (port, text) in columns.iter().zip(row) {
+                let value = match text {
+                    Some(text) => Some(port.parse_value(text)?),
+                    None => None,
+                };
+                expected.push(value);
+            }
+
+            for (port, value) in columns.iter().zip(&expected) {
+                if let Some(value) = value
+                    && port.input()
+                {
+                    dut.set_port(port.name(), *value)?;
+                }
+            }
+
+            dut.tick();
+
+            let mut failed = false;
+            for (port, value) in columns.iter().zip(&expected) {
+                if let Some(value) = value
+                    && port.output()
+                    && !port.input()
+                    && dut.get_port(port.name())? != *value
+                {
+                    failed = true;
+                }
+            }
+
+            if failed {
+                let table = diff_table(dut, &ports, &columns, &expected)?;
+                return err::mismatch!("at cycle {cycle}\n{table}");
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Like `run` but panics with the mismatch report, for use in tests.
+    pub fn check<D: Clocked>(&self, dut: &mut D) {
+        if let Err(e) = self.run(dut) {
+            panic!("{e}");
+        }
+    }
+}
+
+fn cell(text: &str) -> Option<String> {
+    let text = text.trim();
+    if text.is_empty() || text.eq_ignore_ascii_case("x") {
+        None
+    } else {
+        Some(text.to_string())
+    }
+}
+
+fn diff_table<D: Clocked>(
+    dut: &D,
+    ports: &[PortInfo],
+    columns: &[PortInfo],
+    expected: &[Option<Value>],
+) -> err::Result<String> {
+    let mut lines = vec![[
+        "port".to_string(),
+        "expected".into(),
+        "actual".into(),
+        "".into(),
+    ]];
+    for port in ports {
+        let actual = dut.get_port(port.name())?;
+        let wanted = columns
+            .iter()
+            .zip(expected)
+            .find(|(column, _)| column.name() == port.name())
+            .and_then(|(_, value)| *value);
+        let marker = match wanted {
+            Some(wanted) if port.output() && !port.input() && wanted != actual => "<<",
+            _ => "",
+        };
+        lines.push([
+            port.name().to_string(),
+            wanted.map_or("x".into(), |value| value.to_string()),
+            actual.to_string(),
+            marker.into(),
+        ]);
+    }
+
+    let mut widths = [0; 3];
+    for line in &lines {
+        for (width, text) in widths.iter_mut().zip(line) {
+            *width = (*width).max(text.len());
+        }
+    }
+
+    let mut table = String::new();
+    for [name, wanted, actual, marker] in lines {
+        let line = format!(
+            "  {name:<0$}  {wanted:<1$}  {actual:<2$}  {marker}",
+            widths[0], widths[1], widths[2]
+        );
+        table.push_str(line.trim_end());
+        table.push('\n');
+    }
+    Ok(table)
+}
+
+/// Just enough JSON to read one flat object per line.
+mod json {
+    pub type Object = Vec<(String, Option<String>)>;
+
+    pub fn parse_object(text: &str) -> Result<Object, String> {
+        let mut chars = text.trim().chars().peekable();
+        let mut object = vec![];
+
+        expect(&mut chars, '{')?;
+        skip_space(&mut chars);
+        if chars.peek() == Some(&'}') {
+            chars.next();
+        } else {
+            loop {
+                skip_space(&mut chars);
+                let key = string(&mut chars)?;
+                skip_space(&mut chars);
+                expect(&mut chars, ':')?;
+                skip_space(&mut chars);
+                let value = match chars.peek() {
+                    Some('"') => Some(string(&mut chars)?),
+                    _ => {
+                        let word = bare(&mut chars);
+                        match word.as_str() {
+                            "null" => None,
+                            "" => return Err(format!("missing value for '{key}'")),
+                            _ => Some(word),
+                        }
+                    }
+                };
+                object.push((key, value));
+                skip_space(&mut chars);
+                match chars.next() {
+                    Some(',') => continue,
+                    Some('}') => break,
+                    Some(other) => return Err(format!("expected ',' or '}}', found '{other}'")),
+                    None => return Err("unexpected end of line".into()),
+                }
+            }
+        }
+
+        skip_space(&mut chars);
+        match chars.next() {
+            None => Ok(object),
+            Some(other) => Err(format!("unexpected '{other}' after object")),
+        }
+    }
+
+    type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+    fn skip_space(chars: &mut Chars) {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    }
+
+    fn expect(chars: &mut Chars, want: char) -> Result<(), String> {
+        match chars.next() {
+            Some(c) if c == want => Ok(()),
+            Some(other) => Err(format!("expected '{want}', found '{other}'")),
+            None => Err(format!("expected '{want}', found end of line")),
+        }
+    }
+
+    fn string(chars: &mut Chars) -> Result<String, String> {
+        expect(chars, '"')?;
+        let mut s = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => return Ok(s),
+                Some('\\') => match chars.next() {
+                    Some('n') => s.push('\n'),
+                    Some('t') => s.push('\t'),
+                    Some(c @ ('"' | '\\' | '/')) => s.push(c),
+                    Some(other) => return Err(format!("unsupported escape '\\{other}'")),
+                    None => return Err("unterminated string".into()),
+                },
+                Some(c) => s.push(c),
+                None => return Err("unterminated string".into()),
+            }
+        }
+    }
+
+    fn bare(chars: &mut Chars) -> String {
+        let mut s = String::new();
+        while let Some(c) = chars.peek() {
+            if *c == ',' || *c == '}' || c.is_whitespace() {
+                break;
+            }
+            s.push(*c);
+            chars.next();
+        }
+        s
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Dut;
+    use crate::mock::PERIOD;
+    use crate::mock::counter;
+
+    #[test]
+    fn vectors_csv() -> err::Result<()> {
+        let vectors = Vectors::parse_csv(
+            "# a comment\n\
+             enable, value\n\
+             1, 1\n\
+             \n\
+             x, 0x2\n\
+             0, X\n\
+             ,2\n",
+        )?;
+
+        assert_eq!(&["enable", "value"], vectors.columns());
+        assert_eq!(4, vectors.len());
+        assert_eq!(Ok(()), vectors.run(&mut counter()));
+        Ok(())
+    }
+
+    #[test]
+    fn vectors_json_lines() -> err::Result<()> {
+        let vectors = Vectors::parse_json_lines(
+            "{\"enable\": true, \"value\": 1}\n\
+             {\"value\": \"0x02\"}\n\
+             {\"enable\": 0, \"value\": null}\n\
+             {\"value\": \"x\"}\n",
+        )?;
+
+        assert_eq!(&["enable", "value"], vectors.columns());
+        assert_eq!(4, vectors.len());
+        assert_eq!(Ok(()), vectors.run(&mut counter()));
+        Ok(())
+    }
+
+    #[test]
+    fn vectors_mismatch() -> err::Result<()> {
+        let vectors = Vectors::parse_csv("enable,value\n1,1\n1,2\n0,4\n")?;
+
+        let expected = "at cycle 2\n  \
+            port    expected  actual\n  \
+            clk     x         0\n  \
+            enable  0         0\n  \
+            value   0x4       0x2     <<\n";
+        assert_eq!(
+            Err(err::Error::Mismatch(expected.into())),
+            vectors.run(&mut counter())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn vectors_errors() {
+        assert!(Vectors::parse_csv("").is_err());
+        assert!(Vectors::parse_csv("a,b\n1\n").is_err());
+        assert!(Vectors::parse_json_lines("{\"a\": 1").is_err());
+        assert!(Vectors::parse_json_lines("[1, 2]").is_err());
+
+        let unknown = Vectors::parse_csv("missing\n1\n").unwrap();
+        assert!(unknown.run(&mut counter()).is_err());
+
+        let too_wide = Vectors::parse_csv("value\n256\n").unwrap();
+        assert!(too_wide.run(&mut counter()).is_err());
+
+        let output = Vectors::parse_csv("value\n1\n").unwrap();
+        let mut dut = counter();
+        assert!(output.run(&mut dut).is_err());
+        assert_eq!(PERIOD, dut.time());
+    }
+}
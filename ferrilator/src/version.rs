@@ -0,0 +1,233 @@
+use ferrilator_core::err;
+
+/// A Verilator release, as reported by `verilator --version`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    major: u32,
+    minor: u32,
+}
+
+/// The oldest release the build steps are known to work with.
+pub const OLDEST_SUPPORTED: Version = Version::new(4, 38);
+
+/// The first major release the build steps are not known to work with.
+pub const FIRST_UNSUPPORTED_MAJOR: u32 = 6;
+
+impl Version {
+    pub const fn new(major: u32, minor: u32) -> Version {
+        Version { major, minor }
+    }
+
+    pub fn major(&self) -> u32 {
+        self.major
+    }
+
+    pub fn minor(&self) -> u32 {
+        self.minor
+    }
+
+    /// Run `verilator --version` and parse the result.
+    pub fn detect() -> err::Result<Version> {
+        let out = std::process::Command::new("verilator")
+            .arg("--version")
+            .output()?;
+        if !out.status.success() {
+            return err::io!("verilator --version exited with {}", out.status);
+        }
+        Version::parse(&String::from_utf8_lossy(&out.stdout))
+    }
+
+    /// Parse output like `Verilator 5.020 2024-01-01 rev (Debian 5.020-1)`.
+    pub fn parse(text: &str) -> err::Result<Version> {
+        let mut words = text.split_whitespace();
+        if words.next() != Some("Verilator") {
+            return err::input!("unrecognised verilator version '{text}'");
+        }
+        let number = words.next().unwrap_or_default();
+        let (major, minor) = match number.split_once('.') {
+            Some(parts) => parts,
+            None => return err::input!("unrecognised verilator version '{text}'"),
+        };
+        match (major.parse(), minor.parse()) {
+            (Ok(major), Ok(minor)) => Ok(Version { major, minor }),
+            _ => err::input!("unrecognised verilator version '{text}'"),
+        }
+    }
+
+    /// Fail for releases outside the supported range.
+    pub fn check_supported(&self) -> err::Result<()> {
+        if *self < OLDEST_SUPPORTED || self.major >= FIRST_UNSUPPORTED_MAJOR {
+            return err::input!(
+                "verilator {self} is not supported, use {OLDEST_SUPPORTED} up to but excluding {FIRST_UNSUPPORTED_MAJOR}.0"
+            );
+        }
+        Ok(())
+    }
+
+    /// The archive `verilator --build` leaves in the output directory.
+    pub(crate) fn module_archive(&self, module_name: &str) -> String {
+        if self.major >= 5 {
+            format!("libV{module_name}.a")
+        } else {
+            format!("V{module_name}__ALL.a")
+        }
+    }
+
+    /// Runtime sources from the verilator include directory. Verilator 5
+    /// always needs the thread pool, 4 only for models built with
+    /// `--threads`.
+    pub(crate) fn runtime_sources(&self, threads: bool) -> &'static [&'static str] {
+        if self.major >= 5 || threads {
+            &["verilated.cpp", "verilated_threads.cpp"]
+        } else {
+            &["verilated.cpp"]
+        }
+    }
+
+    /// Extra libraries the runtime must be linked against.
+    pub(crate) fn runtime_libs(&self, threads: bool) -> &'static [&'static str] {
+        if self.major >= 5 || threads {
+            &["pthread"]
+        } else {
+            &[]
+        }
+    }
+
+    /// Flags for compiling the runtime and binding. Verilator 4 only
+    /// includes the thread safe runtime when asked.
+    pub(crate) fn cxx_flags(&self, threads: bool) -> &'static [&'static str] {
+        if self.major < 5 && threads {
+            &["-DVL_THREADED", "-pthread"]
+        } else {
+            &[]
+        }
+    }
+
+    /// Verilator 5 sets the number of threads on the `VerilatedContext`,
+    /// 4 fixes it when the model is built.
+    pub(crate) fn context_threads(&self) -> bool {
+        self.major >= 5
+    }
+
+    /// Verilator 5 defaults to `--timing` when the design has delays, but
+    /// time is advanced from Rust so the scheduler is never needed.
+    pub(crate) fn timing_args(&self) -> &'static [&'static str] {
+        if self.major >= 5 {
+            &["--no-timing"]
+        } else {
+            &[]
+        }
+    }
+
+    /// Releases from 4.200 have a `VerilatedContext` for each model, older
+    /// ones keep the simulation state in globals shared by every model.
+    pub(crate) fn has_context(&self) -> bool {
+        *self >= Version::new(4, 200)
+    }
+
+    /// Verilator 4 expects the harness to define `sc_time_stamp`.
+    pub(crate) fn needs_time_stamp(&self) -> bool {
+        self.major < 5
+    }
+}
+
+impl std::fmt::Display for Version {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}.{:03}", self.major, self.minor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Versions we build against, keep in step with the table in the readme.
+    const MATRIX: &[(&str, Version, bool)] = &[
+        (
+            "Verilator 4.038 2020-07-11 rev v4.036-114-g0cd4a57ad",
+            Version::new(4, 38),
+            true,
+        ),
+        (
+            "Verilator 4.200 2021-03-12 rev v4.200",
+            Version::new(4, 200),
+            true,
+        ),
+        (
+            "Verilator 4.228 2022-10-01 rev v4.228",
+            Version::new(4, 228),
+            true,
+        ),
+        (
+            "Verilator 5.020 2024-01-01 rev (Debian 5.020-1)",
+            Version::new(5, 20),
+            true,
+        ),
+        (
+            "Verilator 5.032 2025-01-01 rev (Debian 5.032-1)",
+            Version::new(5, 32),
+            true,
+        ),
+        (
+            "Verilator 3.916 2017-11-25 rev verilator_3_916",
+            Version::new(3, 916),
+            false,
+        ),
+        (
+            "Verilator 6.000 2030-01-01 rev v6.000",
+            Version::new(6, 0),
+            false,
+        ),
+    ];
+
+    #[test]
+    fn version_matrix() -> err::Result<()> {
+        for (text, expected, supported) in MATRIX {
+            let version = Version::parse(text)?;
+            assert_eq!(*expected, version);
+            assert_eq!(*supported, version.check_supported().is_ok(), "{text}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn version_build_steps() {
+        let v4 = Version::new(4, 228);
+        assert_eq!("Vcounter__ALL.a", v4.module_archive("counter"));
+        assert_eq!(&["verilated.cpp"], v4.runtime_sources(false));
+        assert!(v4.runtime_libs(false).is_empty());
+        assert!(v4.cxx_flags(false).is_empty());
+        assert!(v4.timing_args().is_empty());
+        assert!(v4.needs_time_stamp());
+        assert!(!v4.context_threads());
+        assert!(v4.has_context());
+        assert!(!Version::new(4, 38).has_context());
+
+        assert_eq!(
+            &["verilated.cpp", "verilated_threads.cpp"],
+            v4.runtime_sources(true)
+        );
+        assert_eq!(&["pthread"], v4.runtime_libs(true));
+        assert_eq!(&["-DVL_THREADED", "-pthread"], v4.cxx_flags(true));
+
+        let v5 = Version::new(5, 20);
+        assert_eq!("libVcounter.a", v5.module_archive("counter"));
+        assert_eq!(
+            &["verilated.cpp", "verilated_threads.cpp"],
+            v5.runtime_sources(false)
+        );
+        assert_eq!(&["pthread"], v5.runtime_libs(false));
+        assert!(v5.cxx_flags(true).is_empty());
+        assert_eq!(&["--no-timing"], v5.timing_args());
+        assert!(!v5.needs_time_stamp());
+        assert!(v5.context_threads());
+        assert!(v5.has_context());
+    }
+
+    #[test]
+    fn version_parse_errors() {
+        assert!(Version::parse("").is_err());
+        assert!(Version::parse("iverilog 12.0").is_err());
+        assert!(Version::parse("Verilator five").is_err());
+    }
+}
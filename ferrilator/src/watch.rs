@@ -0,0 +1,71 @@
+/// The callbacks registered for changes to one output port, along with the
+/// value they last saw. Generated bindings hold one of these per output, it
+/// is rarely needed directly.
+pub struct OnChange<T> {
+    last: Option<T>,
+    callbacks: Vec<Box<dyn FnMut(T, T) + Send>>,
+}
+
+impl<T: Copy + PartialEq> OnChange<T> {
+    pub fn new() -> OnChange<T> {
+        OnChange {
+            last: None,
+            callbacks: vec![],
+        }
+    }
+
+    /// Whether any callbacks are registered, the port need not be read
+    /// otherwise.
+    pub fn active(&self) -> bool {
+        !self.callbacks.is_empty()
+    }
+
+    /// Add a callback, taking `now` as the value already seen.
+    pub fn register(&mut self, now: T, callback: impl FnMut(T, T) + Send + 'static) {
+        self.last = Some(now);
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Call every callback with the old and new value if the port has
+    /// changed since it was last seen.
+    pub fn update(&mut self, now: T) {
+        match self.last.replace(now) {
+            Some(old) if old != now => {
+                for callback in &mut self.callbacks {
+                    callback(old, now);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+impl<T: Copy + PartialEq> Default for OnChange<T> {
+    fn default() -> Self {
+        OnChange::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[test]
+    fn on_change_update() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut watch = OnChange::new();
+        assert!(!watch.active());
+
+        let log = seen.clone();
+        watch.register(3u8, move |old, new| log.lock().unwrap().push((old, new)));
+        assert!(watch.active());
+
+        watch.update(3);
+        watch.update(4);
+        watch.update(4);
+        watch.update(1);
+        assert_eq!(vec![(3, 4), (4, 1)], *seen.lock().unwrap());
+    }
+}
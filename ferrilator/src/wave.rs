@@ -0,0 +1,1109 @@
+//! Waveforms as text, for printing in a failed test or pasting into a
+//! review. [`Waves`] holds a set of signals sampled at the same points,
+//! taken either from the [`History`] of a design or from a VCD file read
+//! into a [`Trace`], and renders them one line per signal:
+//!
+//! ```text
+//! clk    ▁▁▁▁▁▔▔▔▔▔▁▁▁▁▁▔▔▔▔▔
+//! value  <0x3>=====<0x4>=====
+//! ```
+//!
+//! ```ignore
+//! let trace = Trace::read_vcd("counter.vcd").unwrap();
+//! let waves = Waves::from_trace(&trace).select(&["clk", "value"]).unwrap();
+//! println!("{}", waves.range(0..100_000));
+//! ```
+//!
+//! Waves sampled at the active edges of a clock, numbered from 1 like the
+//! cycles of a design, can be compared with [`compare`], for example to
+//! check a run against a golden waveform from another simulator:
+//!
+//! ```ignore
+//! let golden = Trace::read("golden/counter.vcd").unwrap().at_posedges("clk").unwrap();
+//! let actual = Waves::from_history(dut.history().unwrap());
+//! compare(&golden, &actual, &["value", "overflow"]).unwrap().assert_match();
+//! ```
+
+use crate::History;
+use crate::Value;
+use crate::logic::fmt_logic;
+use ferrilator_core::err;
+use std::collections::HashMap;
+use std::ops::RangeBounds;
+use std::path::Path;
+
+/// The value of a signal in a waveform, which unlike a [`Value`] can have
+/// unknown bits, read from `x` or `z` in a VCD.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Bits {
+    bits: u128,
+    x_mask: u128,
+    width: u32,
+}
+
+impl Bits {
+    /// Bits above `width` are discarded, as are known bits in `x_mask`.
+    pub fn new(bits: u128, x_mask: u128, width: u32) -> Bits {
+        let mask = Value::new(u128::MAX, width).bits();
+        let x_mask = x_mask & mask;
+        Bits {
+            bits: bits & mask & !x_mask,
+            x_mask,
+            width,
+        }
+    }
+
+    /// Every bit unknown.
+    pub fn x(width: u32) -> Bits {
+        Bits::new(0, u128::MAX, width)
+    }
+
+    /// The known bits, unknown bits read as 0.
+    pub fn bits(&self) -> u128 {
+        self.bits
+    }
+
+    /// The unknown bits.
+    pub fn x_mask(&self) -> u128 {
+        self.x_mask
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn is_known(&self) -> bool {
+        self.x_mask == 0
+    }
+
+    /// The value if every bit is known.
+    pub fn value(&self) -> Option<Value> {
+        self.is_known().then(|| Value::new(self.bits, self.width))
+    }
+
+    /// Whether `other` is known and equal wherever these bits are known,
+    /// so unknown bits are don't-cares.
+    pub fn matches(&self, other: Bits) -> bool {
+        let known = !self.x_mask;
+        other.x_mask & known == 0 && other.bits & known == self.bits
+    }
+}
+
+impl From<Value> for Bits {
+    fn from(value: Value) -> Bits {
+        Bits::new(value.bits(), 0, value.width())
+    }
+}
+
+impl std::fmt::Display for Bits {
+    /// Known values are written like a [`Value`], values with every bit
+    /// unknown as `x` and others in binary.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self.value() {
+            Some(value) => write!(f, "{value}"),
+            None if self.x_mask == Bits::x(self.width).x_mask => write!(f, "x"),
+            None => fmt_logic(f, self.bits, self.x_mask, self.width),
+        }
+    }
+}
+
+/// Every change of every signal in a waveform, with times in picoseconds.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trace {
+    signals: Vec<Signal>,
+    wide: Vec<Wide>,
+}
+
+/// The name and width of a variable of more than 128 bits, which is
+/// skipped but reported when it is asked for.
+type Wide = (String, u32);
+
+/// One signal of a [`Trace`], named by its scopes and its own name joined
+/// with `.`, such as `top.counter.value`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signal {
+    name: String,
+    width: u32,
+    changes: Vec<(u64, Bits)>,
+}
+
+impl Trace {
+    /// Read a waveform, VCD is the only format supported. FST files can be
+    /// converted with `fst2vcd`, which comes with GTKWave.
+    pub fn read(path: impl AsRef<Path>) -> err::Result<Trace> {
+        let path = path.as_ref();
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("vcd") => Trace::read_vcd(path),
+            Some("fst") => err::input!(
+                "cannot read {}, FST is not supported, convert it with fst2vcd",
+                path.display()
+            ),
+            _ => err::input!(
+                "cannot tell the format of {} from its extension",
+                path.display()
+            ),
+        }
+    }
+
+    /// Read a VCD file.
+    pub fn read_vcd(path: impl AsRef<Path>) -> err::Result<Trace> {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(text) => Trace::parse_vcd(&text),
+            Err(e) => err::io!("cannot read {}: {e}", path.display()),
+        }
+    }
+
+    /// Parse the text of a VCD file. Real variables are skipped, as are
+    /// variables of more than 128 bits, which are only an error when they
+    /// are looked up by name.
+    pub fn parse_vcd(text: &str) -> err::Result<Trace> {
+        let mut signals: Vec<Signal> = vec![];
+        let mut wide = vec![];
+        // several variables can share an identifier
+        let mut ids: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut scopes = vec![];
+        let mut timescale_fs = 1000;
+        let mut time = 0;
+
+        let mut tokens = text.split_whitespace();
+        while let Some(token) = tokens.next() {
+            match token {
+                "$timescale" => {
+                    timescale_fs = parse_timescale(&until_end(&mut tokens)?.concat())?;
+                }
+                "$scope" => match until_end(&mut tokens)?[..] {
+                    [_, name] => scopes.push(name),
+                    _ => return err::input!("malformed $scope"),
+                },
+                "$upscope" => {
+                    until_end(&mut tokens)?;
+                    scopes.pop();
+                }
+                "$var" => {
+                    let fields = until_end(&mut tokens)?;
+                    let [kind, width, id, name, ..] = fields[..] else {
+                        return err::input!("malformed $var: {}", fields.join(" "));
+                    };
+                    if kind == "real" {
+                        continue;
+                    }
+                    let mut path = scopes.clone();
+                    path.push(name);
+                    let width: u32 = match width.parse() {
+                        Ok(width @ 1..=128) => width,
+                        Ok(width) if width > 128 => {
+                            // its changes are recognised and dropped
+                            ids.entry(id).or_default();
+                            wide.push((path.join("."), width));
+                            continue;
+                        }
+                        _ => return err::input!("variable {name} has {width} bits"),
+                    };
+                    ids.entry(id).or_default().push(signals.len());
+                    signals.push(Signal {
+                        name: path.join("."),
+                        width,
+                        changes: vec![],
+                    });
+                }
+                // values in these sections are ordinary changes
+                "$dumpvars" | "$dumpall" | "$dumpon" | "$dumpoff" | "$end" => {}
+                _ if token.starts_with('$') => {
+                    until_end(&mut tokens)?;
+                }
+                _ if token.starts_with('#') => match token[1..].parse::<u128>() {
+                    Ok(units) => time = (units * timescale_fs / 1000) as u64,
+                    Err(_) => return err::input!("cannot parse time '{token}'"),
+                },
+                _ if token.starts_with(['r', 'R']) => {
+                    tokens.next();
+                }
+                _ => {
+                    let (digits, id) = if token.starts_with(['b', 'B']) {
+                        match tokens.next() {
+                            Some(id) => (&token[1..], id),
+                            None => return err::input!("value {token} has no identifier"),
+                        }
+                    } else {
+                        token.split_at(token.chars().next().unwrap().len_utf8())
+                    };
+                    let Some(indices) = ids.get(id) else {
+                        return err::input!("unknown identifier '{id}' at time {time}");
+                    };
+                    for &index in indices {
+                        let signal = &mut signals[index];
+                        let bits = parse_bits(digits, signal.width)
+                            .map_err(|msg| err::Error::Input(format!("{}: {msg}", signal.name)))?;
+                        signal.change(time, bits);
+                    }
+                }
+            }
+        }
+
+        Ok(Trace { signals, wide })
+    }
+
+    pub fn signals(&self) -> &[Signal] {
+        &self.signals
+    }
+
+    /// Find a signal by its full name, or by the end of its name if that is
+    /// unique, so `value` finds `top.counter.value`.
+    pub fn signal(&self, name: &str) -> err::Result<&Signal> {
+        let names = self.signals.iter().map(|signal| signal.name.as_str());
+        let index = find_name(names, name).map_err(|e| too_wide(&self.wide, name).unwrap_or(e))?;
+        Ok(&self.signals[index])
+    }
+
+    /// Every signal sampled at each rising edge of `clock`, after the
+    /// changes made at the time of the edge. The points count the edges
+    /// from 1, matching [`Dut::cycle`](crate::Dut::cycle) and the cycles of
+    /// [`Waves::from_history`].
+    pub fn at_posedges(&self, clock: &str) -> err::Result<Waves> {
+        self.at_edges(clock, true)
+    }
+
+    /// Every signal sampled at each falling edge of `clock`, as for
+    /// [`at_posedges`](Trace::at_posedges).
+    pub fn at_negedges(&self, clock: &str) -> err::Result<Waves> {
+        self.at_edges(clock, false)
+    }
+
+    fn at_edges(&self, clock: &str, rising: bool) -> err::Result<Waves> {
+        let clock = self.signal(clock)?;
+        if clock.width != 1 {
+            return err::input!("clock {} has {} bits", clock.name, clock.width);
+        }
+        let times = clock.edges(rising);
+        let rows = self
+            .signals
+            .iter()
+            .map(|signal| Row {
+                name: signal.name.clone(),
+                width: signal.width,
+                values: times.iter().map(|time| signal.value_at(*time)).collect(),
+            })
+            .collect();
+        Ok(Waves {
+            points: (1..=times.len() as u64).collect(),
+            rows,
+            wide: self.wide.clone(),
+        })
+    }
+}
+
+impl Signal {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// The time and new value of each change, in time order.
+    pub fn changes(&self) -> &[(u64, Bits)] {
+        &self.changes
+    }
+
+    /// The times this signal went from 0 to 1, or from 1 to 0 when `rising`
+    /// is false. Changes from an unknown value are not edges.
+    pub fn edges(&self, rising: bool) -> Vec<u64> {
+        let (from, to) = if rising { (0, 1) } else { (1, 0) };
+        self.changes
+            .windows(2)
+            .filter(|pair| {
+                pair[0].1.value().is_some_and(|value| value.bits() == from)
+                    && pair[1].1.value().is_some_and(|value| value.bits() == to)
+            })
+            .map(|pair| pair[1].0)
+            .collect()
+    }
+
+    /// The value at `time`, unknown before the first change.
+    pub fn value_at(&self, time: u64) -> Bits {
+        let after = self.changes.partition_point(|(at, _)| *at <= time);
+        match after {
+            0 => Bits::x(self.width),
+            _ => self.changes[after - 1].1,
+        }
+    }
+
+    /// Record a change, replacing one at the same time and skipping values
+    /// that are already held.
+    fn change(&mut self, time: u64, bits: Bits) {
+        if let Some((last_time, last)) = self.changes.last_mut() {
+            if *last_time == time {
+                *last = bits;
+                return;
+            }
+            if *last == bits {
+                return;
+            }
+        }
+        self.changes.push((time, bits));
+    }
+}
+
+/// The tokens up to the next `$end`.
+fn until_end<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> err::Result<Vec<&'a str>> {
+    let mut fields = vec![];
+    for token in tokens {
+        if token == "$end" {
+            return Ok(fields);
+        }
+        fields.push(token);
+    }
+    err::input!("missing $end")
+}
+
+/// The length of a time unit, such as `10ns`, in femtoseconds.
+fn parse_timescale(text: &str) -> err::Result<u128> {
+    let split = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let unit_fs = match unit {
+        "s" => 1_000_000_000_000_000,
+        "ms" => 1_000_000_000_000,
+        "us" => 1_000_000_000,
+        "ns" => 1_000_000,
+        "ps" => 1_000,
+        "fs" => 1,
+        _ => return err::input!("cannot parse timescale '{text}'"),
+    };
+    match number.parse::<u128>() {
+        Ok(number) => Ok(number * unit_fs),
+        Err(_) => err::input!("cannot parse timescale '{text}'"),
+    }
+}
+
+/// Parse the digits of a scalar or vector change. Short values are
+/// extended with 0, or with `x` or `z` if that is the leftmost digit.
+fn parse_bits(digits: &str, width: u32) -> Result<Bits, String> {
+    if digits.is_empty() || digits.len() > width as usize {
+        return Err(format!("cannot parse '{digits}' as {width} bits"));
+    }
+    let extend = match digits.as_bytes()[0] {
+        b'x' | b'X' | b'z' | b'Z' => (0, 1),
+        _ => (0, 0),
+    };
+    let padding = width as usize - digits.len();
+    let mut bits = 0;
+    let mut x_mask = 0;
+    let padded = std::iter::repeat_n(extend, padding);
+    let parsed = digits.chars().map(|digit| match digit {
+        '0' => Ok((0, 0)),
+        '1' => Ok((1, 0)),
+        'x' | 'X' | 'z' | 'Z' => Ok((0, 1)),
+        _ => Err(format!("cannot parse '{digits}' as {width} bits")),
+    });
+    for digit in padded.map(Ok).chain(parsed) {
+        let (bit, x) = digit?;
+        bits = bits << 1 | bit;
+        x_mask = x_mask << 1 | x;
+    }
+    Ok(Bits::new(bits, x_mask, width))
+}
+
+/// The error for looking up `name` when it is one of the `wide` variables
+/// that were skipped.
+fn too_wide(wide: &[Wide], name: &str) -> Option<err::Error> {
+    let index = find_name(wide.iter().map(|(name, _)| name.as_str()), name).ok()?;
+    let (name, width) = &wide[index];
+    Some(err::Error::Input(format!(
+        "signal {name} has {width} bits, 1 to 128 are supported"
+    )))
+}
+
+/// Index of the name that is `name` or ends with `.name`.
+fn find_name<'a>(names: impl Iterator<Item = &'a str>, name: &str) -> err::Result<usize> {
+    let suffix = format!(".{name}");
+    let mut found = None;
+    for (index, candidate) in names.enumerate() {
+        if candidate == name {
+            return Ok(index);
+        }
+        if candidate.ends_with(&suffix) {
+            if found.is_some() {
+                return err::input!("more than one signal is named '{name}'");
+            }
+            found = Some(index);
+        }
+    }
+    match found {
+        Some(index) => Ok(index),
+        None => err::input!("no signal named '{name}'"),
+    }
+}
+
+/// How single bit signals are drawn.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Style {
+    /// `▁▔▁▔`
+    Unicode,
+    /// `_-_-`
+    Ascii,
+}
+
+/// Signals sampled at the same points, cycles or times, for rendering as
+/// text. Signals of one bit are drawn as levels and others as a value
+/// wherever it changes, `<0x3>=====<0x4>`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Waves {
+    points: Vec<u64>,
+    rows: Vec<Row>,
+    wide: Vec<Wide>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Row {
+    name: String,
+    width: u32,
+    values: Vec<Bits>,
+}
+
+impl Waves {
+    /// Every port of a history, sampled at each recorded cycle.
+    pub fn from_history(history: &History) -> Waves {
+        let points = history.samples().map(|sample| sample.cycle()).collect();
+        let rows = history
+            .ports()
+            .iter()
+            .enumerate()
+            .map(|(index, port)| Row {
+                name: port.name().to_string(),
+                width: port.width(),
+                values: history
+                    .samples()
+                    .map(|sample| Bits::from(sample.values()[index]))
+                    .collect(),
+            })
+            .collect();
+        Waves {
+            points,
+            rows,
+            wide: vec![],
+        }
+    }
+
+    /// Every signal of a trace, sampled at each time any of them changes.
+    pub fn from_trace(trace: &Trace) -> Waves {
+        let mut points: Vec<u64> = trace
+            .signals
+            .iter()
+            .flat_map(|signal| signal.changes.iter().map(|(time, _)| *time))
+            .collect();
+        points.sort_unstable();
+        points.dedup();
+        let rows = trace
+            .signals
+            .iter()
+            .map(|signal| Row {
+                name: signal.name.clone(),
+                width: signal.width,
+                values: points.iter().map(|time| signal.value_at(*time)).collect(),
+            })
+            .collect();
+        Waves {
+            points,
+            rows,
+            wide: trace.wide.clone(),
+        }
+    }
+
+    /// The cycles or times sampled.
+    pub fn points(&self) -> &[u64] {
+        &self.points
+    }
+
+    /// The values of one signal at each point. The name is matched as by
+    /// [`Trace::signal`].
+    pub fn values(&self, name: &str) -> err::Result<&[Bits]> {
+        Ok(&self.row(name)?.values)
+    }
+
+    fn row(&self, name: &str) -> err::Result<&Row> {
+        let names = self.rows.iter().map(|row| row.name.as_str());
+        let index = find_name(names, name).map_err(|e| too_wide(&self.wide, name).unwrap_or(e))?;
+        Ok(&self.rows[index])
+    }
+
+    /// Only the named signals, in the order given. Names are matched as by
+    /// [`Trace::signal`].
+    pub fn select(&self, names: &[&str]) -> err::Result<Waves> {
+        let mut rows = vec![];
+        for name in names {
+            rows.push(self.row(name)?.clone());
+        }
+        Ok(Waves {
+            points: self.points.clone(),
+            rows,
+            wide: self.wide.clone(),
+        })
+    }
+
+    /// Only the points within `range`.
+    pub fn range(&self, range: impl RangeBounds<u64>) -> Waves {
+        let keep: Vec<usize> = (0..self.points.len())
+            .filter(|index| range.contains(&self.points[*index]))
+            .collect();
+        Waves {
+            points: keep.iter().map(|index| self.points[*index]).collect(),
+            rows: self
+                .rows
+                .iter()
+                .map(|row| Row {
+                    name: row.name.clone(),
+                    width: row.width,
+                    values: keep.iter().map(|index| row.values[*index]).collect(),
+                })
+                .collect(),
+            wide: self.wide.clone(),
+        }
+    }
+
+    /// One line per signal, each point taking the same number of columns.
+    pub fn render(&self, style: Style) -> String {
+        let (low, high) = match style {
+            Style::Unicode => ('▁', '▔'),
+            Style::Ascii => ('_', '-'),
+        };
+        let column_width = self
+            .rows
+            .iter()
+            .filter(|row| row.width > 1)
+            .flat_map(|row| row.values.iter().map(|value| value.to_string().len() + 2))
+            .max()
+            .unwrap_or(1);
+        let name_width = self
+            .rows
+            .iter()
+            .map(|row| row.name.len())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines = vec![];
+        for row in &self.rows {
+            let mut line = format!("{:name_width$}  ", row.name);
+            let mut last = None;
+            for value in &row.values {
+                if row.width == 1 {
+                    let level = match value.value() {
+                        Some(value) if value.bits() == 1 => high,
+                        Some(_) => low,
+                        None => 'x',
+                    };
+                    line.extend(std::iter::repeat_n(level, column_width));
+                } else if last == Some(value) {
+                    line.extend(std::iter::repeat_n('=', column_width));
+                } else {
+                    let cell = format!("<{value}>");
+                    line.push_str(&format!("{cell:=<column_width$}"));
+                }
+                last = Some(value);
+            }
+            lines.push(line.trim_end().to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+impl std::fmt::Display for Waves {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.render(Style::Unicode))
+    }
+}
+
+/// Compare the named signals of `actual` against `golden` at every point
+/// the two have in common, usually cycles from [`Trace::at_posedges`] or
+/// [`Waves::from_history`]. Unknown bits in `golden` are don't-cares, and
+/// golden points missing from `actual` stop the two from matching, so
+/// compare a [`range`](Waves::range) of `golden` to check part of a run.
+/// Names are matched in each as by [`Trace::signal`], so `value` can stand
+/// for `tb.dut.value` in one and `counter.value` in the other.
+pub fn compare(golden: &Waves, actual: &Waves, signals: &[&str]) -> err::Result<Comparison> {
+    let mut points = vec![];
+    let mut missing = vec![];
+    for (index, point) in golden.points.iter().enumerate() {
+        match actual.points.binary_search(point) {
+            Ok(other) => points.push((index, other)),
+            Err(_) => missing.push(*point),
+        }
+    }
+
+    let mut divergences = vec![];
+    for name in signals {
+        let expected = golden.row(name)?;
+        let found = actual.row(name)?;
+        if expected.width != found.width {
+            return err::mismatch!(
+                "{name} has {} bits in the golden waveform and {} bits in the actual one",
+                expected.width,
+                found.width
+            );
+        }
+        let mut diverging = points
+            .iter()
+            .filter(|(index, other)| !expected.values[*index].matches(found.values[*other]));
+        if let Some(&(index, other)) = diverging.next() {
+            divergences.push(Divergence {
+                signal: name.to_string(),
+                point: golden.points[index],
+                expected: expected.values[index],
+                actual: found.values[other],
+                count: 1 + diverging.count(),
+            });
+        }
+    }
+
+    Ok(Comparison {
+        compared: points.len(),
+        missing,
+        divergences,
+    })
+}
+
+/// The result of [`compare`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comparison {
+    compared: usize,
+    missing: Vec<u64>,
+    divergences: Vec<Divergence>,
+}
+
+/// The first point at which a signal differed from the golden waveform.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    signal: String,
+    point: u64,
+    expected: Bits,
+    actual: Bits,
+    count: usize,
+}
+
+impl Comparison {
+    /// Whether some points were compared, every signal matched and no
+    /// golden point was missing.
+    pub fn is_match(&self) -> bool {
+        self.compared > 0 && self.missing.is_empty() && self.divergences.is_empty()
+    }
+
+    /// Panics describing the divergences, unless the waveforms match.
+    pub fn assert_match(&self) {
+        assert!(self.is_match(), "{self}");
+    }
+
+    /// The number of points found in both waveforms.
+    pub fn compared(&self) -> usize {
+        self.compared
+    }
+
+    /// The golden points not found in the actual waveform.
+    pub fn missing(&self) -> &[u64] {
+        &self.missing
+    }
+
+    /// The first divergence of each signal that differed, in the order the
+    /// signals were given.
+    pub fn divergences(&self) -> &[Divergence] {
+        &self.divergences
+    }
+}
+
+impl std::fmt::Display for Comparison {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.compared == 0 {
+            return write!(f, "the waveforms have no points in common");
+        }
+        if self.is_match() {
+            return write!(f, "the waveforms match at {} points", self.compared);
+        }
+        write!(
+            f,
+            "the waveforms diverge, comparing {} points:",
+            self.compared
+        )?;
+        if let [first, ..] = self.missing[..] {
+            write!(
+                f,
+                "\n  {} golden points are missing from the actual waveform, from {first}",
+                self.missing.len()
+            )?;
+        }
+        for divergence in &self.divergences {
+            write!(f, "\n  {divergence}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Divergence {
+    pub fn signal(&self) -> &str {
+        &self.signal
+    }
+
+    /// The first cycle or time at which the signal differed.
+    pub fn point(&self) -> u64 {
+        self.point
+    }
+
+    /// The golden value.
+    pub fn expected(&self) -> Bits {
+        self.expected
+    }
+
+    pub fn actual(&self) -> Bits {
+        self.actual
+    }
+
+    /// The number of points at which the signal differed.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at {} expected {} but was {}",
+            self.signal, self.point, self.expected, self.actual
+        )?;
+        if self.count > 1 {
+            write!(f, ", {} points differ", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Clocked;
+    use crate::Dut;
+    use crate::PortInfo;
+    use crate::mock::Mock;
+
+    const VCD: &str = "
+$date today $end
+$version some simulator $end
+$timescale 1ns $end
+$scope module top $end
+$var wire 1 ! clk $end
+$var wire 8 \" value [7:0] $end
+$scope module inner $end
+$var wire 8 \" count [7:0] $end
+$var wire 4 # state [3:0] $end
+$var real 64 $ ratio $end
+$upscope $end
+$upscope $end
+$enddefinitions $end
+#0
+$dumpvars
+0!
+bx \"
+bz1 #
+r0.5 $
+$end
+#5
+1!
+b11 \"
+#10
+0!
+b100 #
+#15
+1!
+b100 \"
+";
+
+    #[test]
+    fn wave_bits() {
+        assert_eq!("1", Bits::new(1, 0, 1).to_string());
+        assert_eq!("x", Bits::x(1).to_string());
+        assert_eq!("0xab", Bits::new(0xab, 0, 8).to_string());
+        assert_eq!("x", Bits::x(8).to_string());
+        assert_eq!("4'b1x1x", Bits::new(0b1111, 0b0101, 4).to_string());
+        assert_eq!(Some(Value::from(3u8)), Bits::new(3, 0, 8).value());
+        assert_eq!(None, Bits::new(3, 1, 8).value());
+        assert_eq!(Bits::new(0x12, 0, 8), Bits::from(Value::from(0x12u8)));
+    }
+
+    #[test]
+    fn wave_parse_vcd() -> err::Result<()> {
+        let trace = Trace::parse_vcd(VCD)?;
+        let names: Vec<_> = trace.signals().iter().map(Signal::name).collect();
+        assert_eq!(
+            vec!["top.clk", "top.value", "top.inner.count", "top.inner.state"],
+            names
+        );
+
+        let clk = trace.signal("clk")?;
+        assert_eq!(1, clk.width());
+        assert_eq!(
+            &[
+                (0, Bits::new(0, 0, 1)),
+                (5000, Bits::new(1, 0, 1)),
+                (10000, Bits::new(0, 0, 1)),
+                (15000, Bits::new(1, 0, 1)),
+            ],
+            clk.changes()
+        );
+
+        let value = trace.signal("top.value")?;
+        assert_eq!(Bits::x(8), value.value_at(0));
+        assert_eq!(Bits::new(3, 0, 8), value.value_at(7000));
+        assert_eq!(Bits::new(4, 0, 8), value.value_at(20000));
+        assert_eq!(value.changes(), trace.signal("count")?.changes());
+
+        // short values are extended with z, which reads as unknown
+        let state = trace.signal("inner.state")?;
+        assert_eq!(Bits::new(0b0001, 0b1110, 4), state.value_at(0));
+        assert_eq!(Bits::new(4, 0, 4), state.value_at(10000));
+
+        assert!(trace.signal("ratio").is_err());
+        assert!(trace.signal("missing").is_err());
+
+        let trace = Trace::parse_vcd(
+            "$scope module a $end $var wire 1 ! x $end $upscope $end \
+             $scope module b $end $var wire 1 # x $end $upscope $end",
+        )?;
+        assert!(trace.signal("x").is_err());
+        assert_eq!("b.x", trace.signal("b.x")?.name());
+        Ok(())
+    }
+
+    #[test]
+    fn wave_parse_errors() {
+        let cases = [
+            "$var wire 1 ! clk",
+            "$var wire 1 ! clk $end #0 1%",
+            "$var wire 0 ! empty $end",
+            "$var wire 2 ! pair $end #0 b101 !",
+            "$var wire 2 ! pair $end #0 b12 !",
+            "$timescale 1 parsec $end",
+            "$var wire 1 ! clk $end #x",
+            "$scope module $end",
+            "wat",
+        ];
+        for text in cases {
+            assert!(Trace::parse_vcd(text).is_err(), "{text}");
+        }
+        assert!(Trace::read_vcd("/nonexistent/waves.vcd").is_err());
+    }
+
+    #[test]
+    fn wave_skip_wide() -> err::Result<()> {
+        let trace = Trace::parse_vcd(
+            "$scope module top $end
+             $var wire 1 ! clk $end
+             $var wire 200 \" key [199:0] $end
+             $upscope $end
+             $enddefinitions $end
+             #0 0! b1 \"
+             #5 1! b10 \"",
+        )?;
+        assert_eq!(1, trace.signals().len());
+        let too_wide =
+            err::Error::Input("signal top.key has 200 bits, 1 to 128 are supported".into());
+        assert_eq!(too_wide, trace.signal("key").unwrap_err());
+
+        let waves = trace.at_posedges("clk")?;
+        assert_eq!(&[1], waves.points());
+        assert_eq!(too_wide, waves.select(&["key"]).unwrap_err());
+        assert_eq!(too_wide, compare(&waves, &waves, &["key"]).unwrap_err());
+        assert!(waves.select(&["missing"]).is_err());
+        compare(&waves, &waves, &["clk"])?.assert_match();
+        Ok(())
+    }
+
+    #[test]
+    fn wave_render_trace() -> err::Result<()> {
+        let waves = Waves::from_trace(&Trace::parse_vcd(VCD)?);
+        assert_eq!(&[0, 5000, 10000, 15000], waves.points());
+        assert_eq!(
+            "top.clk          ▁▁▁▁▁▁▁▁▁▔▔▔▔▔▔▔▔▔▁▁▁▁▁▁▁▁▁▔▔▔▔▔▔▔▔▔\n\
+             top.value        <x>======<0x3>=============<0x4>====\n\
+             top.inner.count  <x>======<0x3>=============<0x4>====\n\
+             top.inner.state  <4'bxxx1>=========<0x4>=============",
+            waves.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn wave_select_range() -> err::Result<()> {
+        let waves = Waves::from_trace(&Trace::parse_vcd(VCD)?)
+            .select(&["clk", "value"])?
+            .range(5000..);
+        assert_eq!(&[5000, 10000, 15000], waves.points());
+        assert_eq!(
+            "top.clk    -----_____-----\n\
+             top.value  <0x3>=====<0x4>",
+            waves.render(Style::Ascii)
+        );
+        assert!(waves.select(&["count"]).is_err());
+        Ok(())
+    }
+
+    /// Shifts `data` through a two stage pipeline.
+    const PORTS: [PortInfo; 2] = [
+        PortInfo::new("valid", 1, false, false, true),
+        PortInfo::new("out", 8, false, false, true),
+    ];
+
+    /// The last `depth` of four cycles of a two stage pipeline, which is
+    /// valid from the second cycle.
+    fn pipe_history(depth: usize) -> History {
+        let mut dut = Mock::new(&PORTS).on_eval(|dut| dut.set("valid", (dut.cycle() >= 2).into()));
+        let mut stages = [0; 2];
+        let mut history = History::new(depth);
+        for data in [0x10, 0x20, 0x20, 0x30] {
+            dut.tick();
+            stages = [data, stages[0]];
+            dut.set("out", stages[1]);
+            history.record(&dut);
+        }
+        history
+    }
+
+    #[test]
+    fn wave_render_history() {
+        let waves = Waves::from_history(&pipe_history(8));
+        assert_eq!(&[1, 2, 3, 4], waves.points());
+        assert_eq!(
+            "valid  ▁▁▁▁▁▁▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔▔\n\
+             out    <0x0>=<0x10><0x20>======",
+            waves.to_string()
+        );
+        assert_eq!(
+            "valid  ▁▔▔",
+            waves.select(&["valid"]).unwrap().range(..4).to_string()
+        );
+    }
+
+    #[test]
+    fn wave_edges() -> err::Result<()> {
+        let trace = Trace::parse_vcd(VCD)?;
+        let clk = trace.signal("clk")?;
+        assert_eq!(vec![5000, 15000], clk.edges(true));
+        assert_eq!(vec![10000], clk.edges(false));
+
+        let waves = trace.at_posedges("clk")?;
+        assert_eq!(&[1, 2], waves.points());
+        assert_eq!(
+            &[Bits::new(3, 0, 8), Bits::new(4, 0, 8)],
+            waves.values("value")?
+        );
+        assert_eq!(&[1], trace.at_negedges("clk")?.points());
+        assert!(trace.at_posedges("value").is_err());
+        assert!(trace.at_posedges("missing").is_err());
+        assert!(waves.values("missing").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn wave_read_formats() {
+        assert!(Trace::read("golden.fst").is_err());
+        assert!(Trace::read("golden.txt").is_err());
+        assert!(Trace::read("/nonexistent/golden.vcd").is_err());
+    }
+
+    #[test]
+    fn wave_bits_matches() {
+        let golden = Bits::new(0b1000, 0b0011, 4);
+        assert!(golden.matches(Bits::new(0b1000, 0, 4)));
+        assert!(golden.matches(Bits::new(0b1011, 0, 4)));
+        assert!(!golden.matches(Bits::new(0b0000, 0, 4)));
+        assert!(!golden.matches(Bits::new(0b1000, 0b0100, 4)));
+        assert!(golden.matches(Bits::new(0b1000, 0b0001, 4)));
+    }
+
+    #[test]
+    fn wave_compare() -> err::Result<()> {
+        let golden = Trace::parse_vcd(VCD)?.at_posedges("clk")?;
+        let actual = Trace::parse_vcd(&VCD.replace("b100 \"", "b101 \""))?.at_posedges("clk")?;
+
+        let comparison = compare(&golden, &actual, &["value", "state"])?;
+        assert!(!comparison.is_match());
+        assert_eq!(2, comparison.compared());
+        let divergences = comparison.divergences();
+        assert_eq!(1, divergences.len());
+        assert_eq!("value", divergences[0].signal());
+        assert_eq!(2, divergences[0].point());
+        assert_eq!(Bits::new(4, 0, 8), divergences[0].expected());
+        assert_eq!(Bits::new(5, 0, 8), divergences[0].actual());
+        assert_eq!(1, divergences[0].count());
+        assert_eq!(
+            "the waveforms diverge, comparing 2 points:\n  value at 2 expected 0x4 but was 0x5",
+            comparison.to_string()
+        );
+
+        let comparison = compare(&golden, &golden, &["clk", "value", "state"])?;
+        comparison.assert_match();
+        assert_eq!("the waveforms match at 2 points", comparison.to_string());
+
+        let none = compare(&golden, &actual.range(3..), &["value"])?;
+        assert!(!none.is_match());
+        assert_eq!(&[1, 2], none.missing());
+        assert_eq!("the waveforms have no points in common", none.to_string());
+
+        let short = compare(&golden, &actual.range(2..), &["state"])?;
+        assert!(!short.is_match());
+        assert_eq!(1, short.compared());
+        assert_eq!(&[1], short.missing());
+        assert_eq!(
+            "the waveforms diverge, comparing 1 points:\n  1 golden points are missing from the actual waveform, from 1",
+            short.to_string()
+        );
+        compare(&golden.range(2..), &actual.range(2..), &["state"])?.assert_match();
+
+        assert!(compare(&golden, &actual, &["missing"]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn wave_compare_history() -> err::Result<()> {
+        let golden = Trace::parse_vcd(
+            "$timescale 1ns $end
+             $scope module tb $end
+             $var wire 1 ! clk $end
+             $var wire 1 \" valid $end
+             $var wire 8 # out [7:0] $end
+             $upscope $end
+             $enddefinitions $end
+             #0 0! x\" bx #
+             #5 1! 0\" b0 #
+             #10 0!
+             #15 1! 1\" b10000 #
+             #20 0!
+             #25 1! b100000 #
+             #30 0!
+             #35 1!
+             #40 0!",
+        )?
+        .at_posedges("clk")?;
+
+        let comparison = compare(
+            &golden,
+            &Waves::from_history(&pipe_history(8)),
+            &["valid", "out"],
+        )?;
+        assert_eq!(4, comparison.compared());
+        comparison.assert_match();
+
+        // a short history misses the first cycles, unless they are left out
+        let actual = Waves::from_history(&pipe_history(2));
+        let short = compare(&golden, &actual, &["out"])?;
+        assert_eq!(2, short.compared());
+        assert_eq!(&[1, 2], short.missing());
+        assert!(!short.is_match());
+        compare(&golden.range(3..), &actual, &["out"])?.assert_match();
+
+        let wide = Trace::parse_vcd("$var wire 16 # out $end #0 b0 # #5 b1 #")?;
+        let wide = Waves::from_trace(&wide);
+        assert!(compare(&wide, &actual, &["out"]).is_err());
+        Ok(())
+    }
+}
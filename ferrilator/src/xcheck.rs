@@ -0,0 +1,292 @@
+//! Checks for outputs that depend on unknown values. Verilator simulates two
+//! states, so with the `x_assign` and `x_initial` options set to `unique`
+//! each seed gives X a different value. Running the same stimulus under
+//! several seeds and comparing the outputs finds the bits that depend on X.
+//!
+//! ```ignore
+//! let report = ferrilator::xcheck::run(1..=4, Uninit::with_seed, 10, |dut, cycle| {
+//!     dut.set_load(cycle == 2);
+//!     dut.tick();
+//! })
+//! .unwrap();
+//! report.assert_clean();
+//! ```
+
+use crate::Dut;
+use crate::Logic;
+use crate::PortInfo;
+use crate::Value;
+use crate::logic::fmt_logic;
+use ferrilator_core::err;
+
+/// Create a model for each seed with `new`, then for each of `cycles`
+/// cycles call `stimulus` with the cycle number and sample every output.
+/// `stimulus` drives the inputs and advances the design, it must do the
+/// same for every seed.
+pub fn run<D: Dut>(
+    seeds: impl IntoIterator<Item = u64>,
+    mut new: impl FnMut(u64) -> D,
+    cycles: usize,
+    mut stimulus: impl FnMut(&mut D, usize),
+) -> err::Result<Report> {
+    let seeds: Vec<u64> = seeds.into_iter().collect();
+    if seeds.len() < 2 {
+        return err::input!("an X check needs at least two seeds");
+    }
+
+    let mut ports = vec![];
+    let mut samples = vec![];
+    for &seed in &seeds {
+        let mut dut = new(seed);
+        ports = dut
+            .ports()
+            .iter()
+            .filter(|port| port.output())
+            .copied()
+            .collect();
+        let mut run = Vec::with_capacity(cycles);
+        for cycle in 0..cycles {
+            stimulus(&mut dut, cycle);
+            let values = ports
+                .iter()
+                .map(|port| dut.get_port(port.name()))
+                .collect::<err::Result<Vec<_>>>()?;
+            run.push(values);
+        }
+        dut.finish();
+        samples.push(run);
+    }
+
+    Ok(Report::new(seeds, ports, samples))
+}
+
+/// The outputs sampled by [`run`] for each seed.
+#[derive(Clone, Debug)]
+pub struct Report {
+    seeds: Vec<u64>,
+    ports: Vec<PortInfo>,
+    /// Indexed by seed, cycle then port.
+    samples: Vec<Vec<Vec<Value>>>,
+    differences: Vec<Difference>,
+}
+
+/// An output whose value at a cycle was not the same for every seed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Difference {
+    port: &'static str,
+    cycle: usize,
+    width: u32,
+    bits: u128,
+    x_mask: u128,
+    values: Vec<(u64, Value)>,
+}
+
+impl Report {
+    fn new(seeds: Vec<u64>, ports: Vec<PortInfo>, samples: Vec<Vec<Vec<Value>>>) -> Report {
+        let mut differences = vec![];
+        let cycles = samples.first().map_or(0, |run| run.len());
+        for cycle in 0..cycles {
+            for (index, port) in ports.iter().enumerate() {
+                let first = samples[0][cycle][index].bits();
+                let x_mask = samples
+                    .iter()
+                    .fold(0, |mask, run| mask | (run[cycle][index].bits() ^ first));
+                if x_mask != 0 {
+                    differences.push(Difference {
+                        port: port.name(),
+                        cycle,
+                        width: port.width(),
+                        bits: first & !x_mask,
+                        x_mask,
+                        values: seeds
+                            .iter()
+                            .zip(&samples)
+                            .map(|(seed, run)| (*seed, run[cycle][index]))
+                            .collect(),
+                    });
+                }
+            }
+        }
+        Report {
+            seeds,
+            ports,
+            samples,
+            differences,
+        }
+    }
+
+    /// Whether every output was the same for every seed.
+    pub fn is_clean(&self) -> bool {
+        self.differences.is_empty()
+    }
+
+    /// Panics listing the X dependent outputs, if there are any.
+    pub fn assert_clean(&self) {
+        assert!(self.is_clean(), "{self}");
+    }
+
+    pub fn seeds(&self) -> &[u64] {
+        &self.seeds
+    }
+
+    /// Every output and cycle that differed between seeds, in cycle order.
+    pub fn differences(&self) -> &[Difference] {
+        &self.differences
+    }
+
+    /// The value of output `port` at `cycle`, with the bits that differed
+    /// between seeds unknown.
+    pub fn logic<const N: u32>(&self, port: &str, cycle: usize) -> err::Result<Logic<N>> {
+        let Some(index) = self.ports.iter().position(|info| info.name() == port) else {
+            return err::input!("no output named '{port}'");
+        };
+        if cycle >= self.samples[0].len() {
+            return err::input!("cycle {cycle} was not sampled");
+        }
+        let mut runs = self.samples.iter().map(|run| run[cycle][index]);
+        let mut value = Logic::from_value(runs.next().unwrap())?;
+        for other in runs {
+            value = value.merge(Logic::new(other.bits()));
+        }
+        Ok(value)
+    }
+}
+
+impl std::fmt::Display for Report {
+    /// Lists the first difference of each output.
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if self.is_clean() {
+            return write!(f, "no X dependent outputs across seeds {:?}", self.seeds);
+        }
+        write!(f, "X dependent outputs across seeds {:?}:", self.seeds)?;
+        for port in &self.ports {
+            let mut differences = self
+                .differences
+                .iter()
+                .filter(|difference| difference.port == port.name());
+            if let Some(first) = differences.next() {
+                write!(f, "\n  {first}")?;
+                let more = differences.count();
+                if more > 0 {
+                    write!(f, " and {more} later cycles")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Difference {
+    pub fn port(&self) -> &'static str {
+        self.port
+    }
+
+    pub fn cycle(&self) -> usize {
+        self.cycle
+    }
+
+    /// The bits that were not the same for every seed.
+    pub fn x_mask(&self) -> u128 {
+        self.x_mask
+    }
+
+    /// The value sampled for each seed.
+    pub fn values(&self) -> &[(u64, Value)] {
+        &self.values
+    }
+}
+
+impl std::fmt::Display for Difference {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{} at cycle {} is ", self.port, self.cycle)?;
+        fmt_logic(f, self.bits, self.x_mask, self.width)?;
+        let values: Vec<_> = self
+            .values
+            .iter()
+            .map(|(seed, value)| format!("seed {seed}: {value}"))
+            .collect();
+        write!(f, " ({})", values.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::Mock;
+
+    const PORTS: [PortInfo; 4] = [
+        PortInfo::new("load", 1, false, true, false),
+        PortInfo::new("data", 8, false, true, false),
+        PortInfo::new("value", 8, false, false, true),
+        PortInfo::new("valid", 1, false, false, true),
+    ];
+
+    /// Loads `data` into `value` when `load` is set, the initial value
+    /// comes from the seed, as if it had no reset.
+    fn latch(seed: u64) -> Mock {
+        let mut dut = Mock::new(&PORTS).on_eval(|dut| {
+            if dut.get("load") == 1 {
+                dut.set("value", dut.get("data"));
+                dut.set("valid", 1);
+            }
+        });
+        dut.set("value", seed.into());
+        dut
+    }
+
+    fn load_at(cycle: usize) -> impl FnMut(&mut Mock, usize) {
+        move |dut, now| {
+            dut.set_port("load", Value::from(now == cycle)).unwrap();
+            dut.set_port("data", Value::from(0x5au8)).unwrap();
+            dut.eval();
+        }
+    }
+
+    #[test]
+    fn xcheck_differences() -> err::Result<()> {
+        let report = run([0x10, 0x11, 0x13], latch, 4, load_at(2))?;
+        assert!(!report.is_clean());
+        assert_eq!(&[0x10, 0x11, 0x13], report.seeds());
+
+        let differences = report.differences();
+        assert_eq!(2, differences.len());
+        assert_eq!("value", differences[0].port());
+        assert_eq!(0, differences[0].cycle());
+        assert_eq!(0b11, differences[0].x_mask());
+        assert_eq!((0x11, Value::from(0x11u8)), differences[0].values()[1]);
+        assert_eq!(1, differences[1].cycle());
+
+        assert_eq!("8'b000100xx", report.logic::<8>("value", 0)?.to_string());
+        assert_eq!(Some(0x5a), report.logic::<8>("value", 2)?.known());
+        assert_eq!(Some(0), report.logic::<1>("valid", 0)?.known());
+        assert!(report.logic::<4>("value", 0).is_err());
+        assert!(report.logic::<8>("data", 0).is_err());
+        assert!(report.logic::<8>("value", 4).is_err());
+
+        assert_eq!(
+            "X dependent outputs across seeds [16, 17, 19]:\n  value at cycle 0 is 8'b000100xx (seed 16: 0x10, seed 17: 0x11, seed 19: 0x13) and 1 later cycles",
+            report.to_string()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn xcheck_clean() -> err::Result<()> {
+        let report = run(1..=3, latch, 4, load_at(0))?;
+        assert!(report.is_clean());
+        report.assert_clean();
+        assert_eq!(
+            "no X dependent outputs across seeds [1, 2, 3]",
+            report.to_string()
+        );
+
+        assert!(run([1], latch, 4, load_at(0)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "value at cycle 0")]
+    fn xcheck_assert_clean() {
+        run(1..=2, latch, 1, load_at(1)).unwrap().assert_clean();
+    }
+}
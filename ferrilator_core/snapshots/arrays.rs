@@ -0,0 +1,184 @@
+pub struct Example {
+    dut: *mut (),
+    time: u64,
+    watch_wide: ::ferrilator::OnChange<[u128; 2usize]>,
+}
+impl Example {
+    fn new() -> Self {
+        Self::with_config(&::ferrilator::Config::new())
+    }
+    fn with_threads(threads: u32) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+    }
+    fn with_seed(seed: u64) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+    }
+    fn with_args(args: &[&str]) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_args(args))
+    }
+    fn with_config(config: &::ferrilator::Config) -> Self {
+        let args = config.c_args();
+        let dut = unsafe { ex_module_new(config.threads(), args.argc(), args.argv()) };
+        if let (Some(seed), Some(verilator_seed)) = (
+            config.seed(),
+            config.verilator_seed(),
+        ) {
+            eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+        }
+        let mut dut = Self {
+            dut,
+            time: 0,
+            watch_wide: ::ferrilator::OnChange::new(),
+        };
+        dut.eval();
+        dut
+    }
+    fn eval(&mut self) {
+        unsafe { ex_module_eval(self.dut) };
+        if self.watch_wide.active() {
+            let now = self.get_wide();
+            self.watch_wide.update(now);
+        }
+    }
+    fn set_data_at(&mut self, index: usize, value: u8) {
+        assert!(index < 4usize, "index {index} out of range for data of length 4");
+        unsafe { ex_module_set_data(self.dut, index, value) };
+    }
+    fn set_data(&mut self, values: [u8; 4usize]) {
+        for (index, value) in values.into_iter().enumerate() {
+            self.set_data_at(index, value);
+        }
+    }
+    fn get_wide_at(&self, index: usize) -> u128 {
+        assert!(index < 2usize, "index {index} out of range for wide of length 2");
+        {
+            let mut arr = [0u32; 4];
+            unsafe { ex_module_get_wide(self.dut, index, &mut arr) };
+            (arr[0] as u128) | ((arr[1] as u128) << 32) | ((arr[2] as u128) << 64)
+                | ((arr[3] as u128) << 96)
+        }
+    }
+    fn get_wide(&self) -> [u128; 2usize] {
+        ::std::array::from_fn(|index| self.get_wide_at(index))
+    }
+    fn on_change_wide(
+        &mut self,
+        callback: impl FnMut([u128; 2usize], [u128; 2usize]) + Send + 'static,
+    ) {
+        let now = self.get_wide();
+        self.watch_wide.register(now, callback);
+    }
+}
+impl ::ferrilator::Dut for Example {
+    fn eval(&mut self) {
+        Self::eval(self);
+    }
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn finish(&mut self) {
+        unsafe { ex_module_final(self.dut) };
+    }
+    fn ports(&self) -> &[::ferrilator::PortInfo] {
+        const PORTS: &[::ferrilator::PortInfo] = &[
+            ::ferrilator::PortInfo::new("data[0]", 8u32, false, true, false),
+            ::ferrilator::PortInfo::new("data[1]", 8u32, false, true, false),
+            ::ferrilator::PortInfo::new("data[2]", 8u32, false, true, false),
+            ::ferrilator::PortInfo::new("data[3]", 8u32, false, true, false),
+            ::ferrilator::PortInfo::new("wide[0]", 128u32, false, false, true),
+            ::ferrilator::PortInfo::new("wide[1]", 128u32, false, false, true),
+        ];
+        PORTS
+    }
+    fn set_port(
+        &mut self,
+        name: &str,
+        value: ::ferrilator::Value,
+    ) -> ::ferrilator::err::Result<()> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)?;
+        match name {
+            "data[0]" => self.set_data_at(0usize, value.bits() as u8),
+            "data[1]" => self.set_data_at(1usize, value.bits() as u8),
+            "data[2]" => self.set_data_at(2usize, value.bits() as u8),
+            "data[3]" => self.set_data_at(3usize, value.bits() as u8),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?;
+        Ok(
+            match name {
+                "data[0]" => {
+                    let index = 0usize;
+                    ::ferrilator::Value::from(unsafe {
+                        ex_module_get_data(self.dut, index)
+                    })
+                }
+                "data[1]" => {
+                    let index = 1usize;
+                    ::ferrilator::Value::from(unsafe {
+                        ex_module_get_data(self.dut, index)
+                    })
+                }
+                "data[2]" => {
+                    let index = 2usize;
+                    ::ferrilator::Value::from(unsafe {
+                        ex_module_get_data(self.dut, index)
+                    })
+                }
+                "data[3]" => {
+                    let index = 3usize;
+                    ::ferrilator::Value::from(unsafe {
+                        ex_module_get_data(self.dut, index)
+                    })
+                }
+                "wide[0]" => {
+                    let index = 0usize;
+                    ::ferrilator::Value::from({
+                        let mut arr = [0u32; 4];
+                        unsafe { ex_module_get_wide(self.dut, index, &mut arr) };
+                        (arr[0] as u128) | ((arr[1] as u128) << 32)
+                            | ((arr[2] as u128) << 64) | ((arr[3] as u128) << 96)
+                    })
+                }
+                "wide[1]" => {
+                    let index = 1usize;
+                    ::ferrilator::Value::from({
+                        let mut arr = [0u32; 4];
+                        unsafe { ex_module_get_wide(self.dut, index, &mut arr) };
+                        (arr[0] as u128) | ((arr[1] as u128) << 32)
+                            | ((arr[2] as u128) << 64) | ((arr[3] as u128) << 96)
+                    })
+                }
+                _ => unreachable!(),
+            },
+        )
+    }
+}
+#[cfg(not(ferrilator_global_context))]
+unsafe impl Send for Example {}
+#[cfg(not(ferrilator_global_context))]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<::ferrilator::OnChange<[u128; 2usize]>>();
+};
+impl Drop for Example {
+    fn drop(&mut self) {
+        unsafe { ex_module_del(self.dut) };
+    }
+}
+#[link(name = "Vex_module")]
+unsafe extern "C" {
+    fn ex_module_new(
+        threads: u32,
+        argc: i32,
+        argv: *const *const ::std::ffi::c_char,
+    ) -> *mut ();
+    fn ex_module_del(dut: *mut ());
+    fn ex_module_eval(dut: *mut ());
+    fn ex_module_final(dut: *mut ());
+    fn ex_module_set_data(dut: *mut (), index: usize, value: u8);
+    fn ex_module_get_data(dut: *mut (), index: usize) -> u8;
+    fn ex_module_get_wide(dut: *mut (), index: usize, blocks: &mut [u32]);
+}
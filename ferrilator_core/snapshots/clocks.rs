@@ -0,0 +1,170 @@
+pub struct Example {
+    dut: *mut (),
+    time: u64,
+    clocks: ::ferrilator::Scheduler,
+    watch_b: ::ferrilator::OnChange<u32>,
+}
+impl Example {
+    fn new() -> Self {
+        Self::with_config(&::ferrilator::Config::new())
+    }
+    fn with_threads(threads: u32) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+    }
+    fn with_seed(seed: u64) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+    }
+    fn with_args(args: &[&str]) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_args(args))
+    }
+    fn with_config(config: &::ferrilator::Config) -> Self {
+        let args = config.c_args();
+        let dut = unsafe { ex_module_new(config.threads(), args.argc(), args.argv()) };
+        if let (Some(seed), Some(verilator_seed)) = (
+            config.seed(),
+            config.verilator_seed(),
+        ) {
+            eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+        }
+        let mut dut = Self {
+            dut,
+            time: 0,
+            clocks: ::ferrilator::Scheduler::new(&[(1000u64, false), (2500u64, true)]),
+            watch_b: ::ferrilator::OnChange::new(),
+        };
+        dut.set_bus_clk(1);
+        dut.eval();
+        dut
+    }
+    fn eval(&mut self) {
+        unsafe { ex_module_eval(self.dut) };
+        if self.watch_b.active() {
+            let now = self.get_b();
+            self.watch_b.update(now);
+        }
+    }
+    fn step(&mut self) {
+        let (time, edges) = self.clocks.advance();
+        self.time = time;
+        for (index, level) in edges {
+            match index {
+                0usize => self.set_core_clk(level),
+                1usize => self.set_bus_clk(level as u8),
+                _ => unreachable!(),
+            }
+        }
+        self.eval();
+    }
+    fn run_for(&mut self, ns: u64) {
+        let end = self.time + ns * 1000;
+        while self.clocks.next_edge().is_some_and(|next| next <= end) {
+            self.step();
+        }
+        self.time = end;
+    }
+    fn set_core_clk(&mut self, value: bool) {
+        unsafe { ex_module_set_core_clk(self.dut, value) };
+    }
+    fn set_bus_clk(&mut self, value: u8) {
+        unsafe { ex_module_set_bus_clk(self.dut, value) };
+    }
+    fn get_b(&self) -> u32 {
+        unsafe { ex_module_get_b(self.dut) }
+    }
+    fn on_change_b(&mut self, callback: impl FnMut(u32, u32) + Send + 'static) {
+        let now = self.get_b();
+        self.watch_b.register(now, callback);
+    }
+}
+impl ::ferrilator::Dut for Example {
+    fn eval(&mut self) {
+        Self::eval(self);
+    }
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn cycle(&self) -> u64 {
+        self.clocks.cycles(0)
+    }
+    fn finish(&mut self) {
+        unsafe { ex_module_final(self.dut) };
+    }
+    fn ports(&self) -> &[::ferrilator::PortInfo] {
+        const PORTS: &[::ferrilator::PortInfo] = &[
+            ::ferrilator::PortInfo::new("core_clk", 1u32, false, true, false),
+            ::ferrilator::PortInfo::new("bus_clk", 8u32, false, true, false),
+            ::ferrilator::PortInfo::new("b", 32u32, false, false, true),
+        ];
+        PORTS
+    }
+    fn set_port(
+        &mut self,
+        name: &str,
+        value: ::ferrilator::Value,
+    ) -> ::ferrilator::err::Result<()> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)?;
+        match name {
+            "core_clk" => self.set_core_clk(value.bits() != 0),
+            "bus_clk" => self.set_bus_clk(value.bits() as u8),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?;
+        Ok(
+            match name {
+                "core_clk" => {
+                    ::ferrilator::Value::from(unsafe {
+                        ex_module_get_core_clk(self.dut)
+                    })
+                }
+                "bus_clk" => {
+                    ::ferrilator::Value::from(unsafe { ex_module_get_bus_clk(self.dut) })
+                }
+                "b" => ::ferrilator::Value::from(unsafe { ex_module_get_b(self.dut) }),
+                _ => unreachable!(),
+            },
+        )
+    }
+}
+impl ::ferrilator::Scheduled for Example {
+    fn clocks(&self) -> &[&'static str] {
+        &["core_clk", "bus_clk"]
+    }
+    fn step(&mut self) {
+        Self::step(self);
+    }
+    fn run_for(&mut self, ns: u64) {
+        Self::run_for(self, ns);
+    }
+}
+#[cfg(not(ferrilator_global_context))]
+unsafe impl Send for Example {}
+#[cfg(not(ferrilator_global_context))]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<::ferrilator::Scheduler>();
+    assert_send::<::ferrilator::OnChange<u32>>();
+};
+impl Drop for Example {
+    fn drop(&mut self) {
+        unsafe { ex_module_del(self.dut) };
+    }
+}
+#[link(name = "Vex_module")]
+unsafe extern "C" {
+    fn ex_module_new(
+        threads: u32,
+        argc: i32,
+        argv: *const *const ::std::ffi::c_char,
+    ) -> *mut ();
+    fn ex_module_del(dut: *mut ());
+    fn ex_module_eval(dut: *mut ());
+    fn ex_module_final(dut: *mut ());
+    fn ex_module_set_core_clk(dut: *mut (), value: bool);
+    fn ex_module_get_core_clk(dut: *mut ()) -> bool;
+    fn ex_module_set_bus_clk(dut: *mut (), value: u8);
+    fn ex_module_get_bus_clk(dut: *mut ()) -> u8;
+    fn ex_module_get_b(dut: *mut ()) -> u32;
+}
@@ -0,0 +1,53 @@
+fn ref_model(
+    a: u32,
+    flag: bool,
+    wide: u128,
+    name: &str,
+    values: &[i32],
+    results: &mut [u64],
+    total: &mut i64,
+    done: &mut bool,
+    big: &mut u128,
+    state: Logic<40>,
+    next: &mut ferrilator::Logic<3>,
+) -> u64 {
+    0
+}
+const _: () = {
+    #[unsafe(export_name = "ref_model")]
+    unsafe extern "C" fn dpi(
+        arg0: u32,
+        arg1: u8,
+        arg2: *const u32,
+        arg3: *const ::std::ffi::c_char,
+        arg4: ::ferrilator::dpi::OpenArrayHandle,
+        arg5: ::ferrilator::dpi::OpenArrayHandle,
+        arg6: *mut i64,
+        arg7: *mut u8,
+        arg8: *mut u32,
+        arg9: *const ::ferrilator::dpi::LogicVecVal,
+        arg10: *mut ::ferrilator::dpi::LogicVecVal,
+    ) -> u64 {
+        let arg3_value = unsafe { ::std::ffi::CStr::from_ptr(arg3) }.to_string_lossy();
+        let mut arg7_value = unsafe { *arg7 == 1 };
+        let mut arg8_value = unsafe { ::ferrilator::dpi::read_wide(arg8, 128u32) };
+        let mut arg10_value = unsafe { ::ferrilator::dpi::read_logic(arg10) };
+        let result = ref_model(
+            arg0,
+            arg1 == 1,
+            unsafe { ::ferrilator::dpi::read_wide(arg2, 96u32) },
+            &arg3_value,
+            unsafe { ::ferrilator::dpi::open_array::<i32>(arg4) },
+            unsafe { ::ferrilator::dpi::open_array_mut::<u64>(arg5) },
+            unsafe { &mut *arg6 },
+            &mut arg7_value,
+            &mut arg8_value,
+            unsafe { ::ferrilator::dpi::read_logic(arg9) },
+            &mut arg10_value,
+        );
+        unsafe { *arg7 = arg7_value as u8 };
+        unsafe { ::ferrilator::dpi::write_wide(arg8, 128u32, arg8_value) };
+        unsafe { ::ferrilator::dpi::write_logic(arg10, arg10_value) };
+        result
+    }
+};
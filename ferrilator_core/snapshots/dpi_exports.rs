@@ -0,0 +1,62 @@
+#[allow(dead_code)]
+impl Ram {
+    fn preload(&mut self, index: i32, value: u128) {
+        unsafe extern "C" {
+            fn preload(index: i32, value: *const u32);
+        }
+        unsafe { ram_dpi_scope(self.dut) };
+        let mut __c1 = [0u32; 4];
+        unsafe { ::ferrilator::dpi::write_wide(__c1.as_mut_ptr(), 40u32, value) };
+        unsafe { preload(index, __c1.as_ptr()) }
+    }
+    fn peek(&mut self, index: i32) -> u32 {
+        unsafe extern "C" {
+            fn peek(index: i32) -> u32;
+        }
+        unsafe { ram_dpi_scope(self.dut) };
+        unsafe { peek(index) }
+    }
+    fn dump(&mut self, words: &mut u128, ok: &mut bool, count: &mut i64) -> i32 {
+        unsafe extern "C" {
+            fn dump(words: *mut u32, ok: *mut u8, count: *mut i64) -> i32;
+        }
+        unsafe { ram_dpi_scope(self.dut) };
+        let mut __c0 = [0u32; 4];
+        unsafe { ::ferrilator::dpi::write_wide(__c0.as_mut_ptr(), 128u32, *words) };
+        let mut __c1 = *ok as u8;
+        let __result = unsafe { dump(__c0.as_mut_ptr(), &mut __c1, count) };
+        *words = unsafe { ::ferrilator::dpi::read_wide(__c0.as_ptr(), 128u32) };
+        *ok = __c1 == 1;
+        __result
+    }
+    fn named(&mut self, name: &str, enable: bool) -> bool {
+        unsafe extern "C" {
+            fn named(name: *const ::std::ffi::c_char, enable: u8) -> u8;
+        }
+        unsafe { ram_dpi_scope(self.dut) };
+        let __c0 = ::std::ffi::CString::new(name)
+            .expect("DPI string arguments cannot contain nul bytes");
+        let __result = unsafe { named(__c0.as_ptr(), enable as u8) };
+        __result == 1
+    }
+    fn checksum(
+        &mut self,
+        result: i32,
+        c_result: u128,
+        __arg2: i32,
+        __arg3: i32,
+    ) -> i32 {
+        unsafe extern "C" {
+            fn checksum(
+                result: i32,
+                c_result: *const u32,
+                __arg2: i32,
+                __arg3: i32,
+            ) -> i32;
+        }
+        unsafe { ram_dpi_scope(self.dut) };
+        let mut __c1 = [0u32; 4];
+        unsafe { ::ferrilator::dpi::write_wide(__c1.as_mut_ptr(), 8u32, c_result) };
+        unsafe { checksum(result, __c1.as_ptr(), __arg2, __arg3) }
+    }
+}
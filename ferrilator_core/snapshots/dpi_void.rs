@@ -0,0 +1,7 @@
+fn poke(addr: u32, data: u8) {}
+const _: () = {
+    #[unsafe(export_name = "poke")]
+    unsafe extern "C" fn dpi(arg0: u32, arg1: u8) {
+        poke(arg0, arg1);
+    }
+};
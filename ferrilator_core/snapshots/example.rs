@@ -1,20 +1,98 @@
 pub struct Example {
     dut: *mut (),
+    time: u64,
+    clocks: ::ferrilator::Scheduler,
+    history: Option<::ferrilator::History>,
+    watch_b: ::ferrilator::OnChange<u64>,
 }
 impl Example {
     fn new() -> Self {
-        let dut = unsafe { ex_module_new() };
-        Self { dut }
+        Self::with_config(&::ferrilator::Config::new())
+    }
+    fn with_threads(threads: u32) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+    }
+    fn with_seed(seed: u64) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+    }
+    fn with_args(args: &[&str]) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_args(args))
+    }
+    fn with_config(config: &::ferrilator::Config) -> Self {
+        let args = config.c_args();
+        let dut = unsafe { ex_module_new(config.threads(), args.argc(), args.argv()) };
+        if let (Some(seed), Some(verilator_seed)) = (
+            config.seed(),
+            config.verilator_seed(),
+        ) {
+            eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+        }
+        let mut dut = Self {
+            dut,
+            time: 0,
+            clocks: ::ferrilator::Scheduler::new(&[(10000u64, false)]),
+            history: None,
+            watch_b: ::ferrilator::OnChange::new(),
+        };
+        dut.eval();
+        dut
     }
     fn eval(&mut self) {
         unsafe { ex_module_eval(self.dut) };
+        if self.watch_b.active() {
+            let now = self.get_b();
+            self.watch_b.update(now);
+        }
     }
-    fn tick(&mut self) {
+    fn step(&mut self) {
+        let (time, edges) = self.clocks.advance();
+        self.time = time;
+        for (index, level) in edges {
+            match index {
+                0usize => self.set_clk(level),
+                _ => unreachable!(),
+            }
+        }
+        self.eval();
+    }
+    fn run_for(&mut self, ns: u64) {
+        let end = self.time + ns * 1000;
+        while self.clocks.next_edge().is_some_and(|next| next <= end) {
+            self.step();
+        }
+        self.time = end;
+    }
+    fn posedge(&mut self) {
         self.set_clk(true);
+        self.time += 5000u64;
+        self.clocks.drive(0, true, self.time);
         self.eval();
+    }
+    fn negedge(&mut self) {
         self.set_clk(false);
+        self.time += 5000u64;
+        self.clocks.drive(0, false, self.time);
         self.eval();
     }
+    fn tick(&mut self) {
+        self.posedge();
+        self.negedge();
+        if let Some(mut history) = self.history.take() {
+            history.record(self);
+            self.history = Some(history);
+        }
+    }
+    fn tick_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    fn record_history(&mut self, cycles: usize) {
+        self.history = Some(::ferrilator::History::new(cycles));
+    }
+    fn history(&self) -> Option<&::ferrilator::History> {
+        self.history.as_ref()
+    }
     fn set_clk(&mut self, value: bool) {
         unsafe { ex_module_set_clk(self.dut, value) };
     }
@@ -24,18 +102,111 @@ impl Example {
     fn get_b(&self) -> u64 {
         unsafe { ex_module_get_b(self.dut) }
     }
+    fn on_change_b(&mut self, callback: impl FnMut(u64, u64) + Send + 'static) {
+        let now = self.get_b();
+        self.watch_b.register(now, callback);
+    }
+}
+impl ::ferrilator::Dut for Example {
+    fn eval(&mut self) {
+        Self::eval(self);
+    }
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn cycle(&self) -> u64 {
+        self.clocks.cycles(0)
+    }
+    fn finish(&mut self) {
+        unsafe { ex_module_final(self.dut) };
+    }
+    fn ports(&self) -> &[::ferrilator::PortInfo] {
+        const PORTS: &[::ferrilator::PortInfo] = &[
+            ::ferrilator::PortInfo::new("clk", 1u32, false, true, false),
+            ::ferrilator::PortInfo::new("a", 8u32, false, true, false),
+            ::ferrilator::PortInfo::new("b", 64u32, false, false, true),
+        ];
+        PORTS
+    }
+    fn set_port(
+        &mut self,
+        name: &str,
+        value: ::ferrilator::Value,
+    ) -> ::ferrilator::err::Result<()> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)?;
+        match name {
+            "clk" => self.set_clk(value.bits() != 0),
+            "a" => self.set_a(value.bits() as u8),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?;
+        Ok(
+            match name {
+                "clk" => {
+                    ::ferrilator::Value::from(unsafe { ex_module_get_clk(self.dut) })
+                }
+                "a" => ::ferrilator::Value::from(unsafe { ex_module_get_a(self.dut) }),
+                "b" => ::ferrilator::Value::from(unsafe { ex_module_get_b(self.dut) }),
+                _ => unreachable!(),
+            },
+        )
+    }
+}
+impl ::ferrilator::Scheduled for Example {
+    fn clocks(&self) -> &[&'static str] {
+        &["clk"]
+    }
+    fn step(&mut self) {
+        Self::step(self);
+    }
+    fn run_for(&mut self, ns: u64) {
+        Self::run_for(self, ns);
+    }
+}
+impl ::ferrilator::Clocked for Example {
+    fn posedge(&mut self) {
+        Self::posedge(self);
+    }
+    fn negedge(&mut self) {
+        Self::negedge(self);
+    }
+    fn tick(&mut self) {
+        Self::tick(self);
+    }
 }
+#[cfg(not(ferrilator_global_context))]
+unsafe impl Send for Example {}
+#[cfg(not(ferrilator_global_context))]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<::ferrilator::Scheduler>();
+    assert_send::<Option<::ferrilator::History>>();
+    assert_send::<::ferrilator::OnChange<u64>>();
+};
 impl Drop for Example {
     fn drop(&mut self) {
+        if let Some(history) = &self.history {
+            history.print_if_panicking();
+        }
         unsafe { ex_module_del(self.dut) };
     }
 }
 #[link(name = "Vex_module")]
 unsafe extern "C" {
-    fn ex_module_new() -> *mut ();
+    fn ex_module_new(
+        threads: u32,
+        argc: i32,
+        argv: *const *const ::std::ffi::c_char,
+    ) -> *mut ();
     fn ex_module_del(dut: *mut ());
     fn ex_module_eval(dut: *mut ());
+    fn ex_module_final(dut: *mut ());
     fn ex_module_set_clk(dut: *mut (), value: bool);
+    fn ex_module_get_clk(dut: *mut ()) -> bool;
     fn ex_module_set_a(dut: *mut (), value: u8);
+    fn ex_module_get_a(dut: *mut ()) -> u8;
     fn ex_module_get_b(dut: *mut ()) -> u64;
 }
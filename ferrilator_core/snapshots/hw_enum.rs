@@ -0,0 +1,19 @@
+impl ::ferrilator::PortType for State {
+    const WIDTH: u32 = 3u32;
+    type Read = ::std::result::Result<State, u64>;
+    fn to_bits(&self) -> u128 {
+        match self {
+            State::Idle => 0u128,
+            State::Busy => 1u128,
+            State::Done => 4u128,
+        }
+    }
+    fn from_bits(bits: u128) -> Self::Read {
+        match (bits & 7u128) as u64 {
+            0u64 => Ok(State::Idle),
+            1u64 => Ok(State::Busy),
+            4u64 => Ok(State::Done),
+            other => Err(other),
+        }
+    }
+}
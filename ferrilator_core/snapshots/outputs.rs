@@ -0,0 +1,107 @@
+pub struct Example {
+    dut: *mut (),
+    time: u64,
+    watch_b: ::ferrilator::OnChange<u8>,
+}
+impl Example {
+    fn new() -> Self {
+        Self::with_config(&::ferrilator::Config::new())
+    }
+    fn with_threads(threads: u32) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+    }
+    fn with_seed(seed: u64) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+    }
+    fn with_args(args: &[&str]) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_args(args))
+    }
+    fn with_config(config: &::ferrilator::Config) -> Self {
+        let args = config.c_args();
+        let dut = unsafe { ex_module_new(config.threads(), args.argc(), args.argv()) };
+        if let (Some(seed), Some(verilator_seed)) = (
+            config.seed(),
+            config.verilator_seed(),
+        ) {
+            eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+        }
+        let mut dut = Self {
+            dut,
+            time: 0,
+            watch_b: ::ferrilator::OnChange::new(),
+        };
+        dut.eval();
+        dut
+    }
+    fn eval(&mut self) {
+        unsafe { ex_module_eval(self.dut) };
+        if self.watch_b.active() {
+            let now = self.get_b();
+            self.watch_b.update(now);
+        }
+    }
+    fn get_b(&self) -> u8 {
+        unsafe { ex_module_get_b(self.dut) }
+    }
+    fn on_change_b(&mut self, callback: impl FnMut(u8, u8) + Send + 'static) {
+        let now = self.get_b();
+        self.watch_b.register(now, callback);
+    }
+}
+impl ::ferrilator::Dut for Example {
+    fn eval(&mut self) {
+        Self::eval(self);
+    }
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn finish(&mut self) {
+        unsafe { ex_module_final(self.dut) };
+    }
+    fn ports(&self) -> &[::ferrilator::PortInfo] {
+        const PORTS: &[::ferrilator::PortInfo] = &[
+            ::ferrilator::PortInfo::new("b", 8u32, false, false, true),
+        ];
+        PORTS
+    }
+    fn set_port(
+        &mut self,
+        name: &str,
+        value: ::ferrilator::Value,
+    ) -> ::ferrilator::err::Result<()> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)
+    }
+    fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?;
+        Ok(
+            match name {
+                "b" => ::ferrilator::Value::from(unsafe { ex_module_get_b(self.dut) }),
+                _ => unreachable!(),
+            },
+        )
+    }
+}
+#[cfg(not(ferrilator_global_context))]
+unsafe impl Send for Example {}
+#[cfg(not(ferrilator_global_context))]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<::ferrilator::OnChange<u8>>();
+};
+impl Drop for Example {
+    fn drop(&mut self) {
+        unsafe { ex_module_del(self.dut) };
+    }
+}
+#[link(name = "Vex_module")]
+unsafe extern "C" {
+    fn ex_module_new(
+        threads: u32,
+        argc: i32,
+        argv: *const *const ::std::ffi::c_char,
+    ) -> *mut ();
+    fn ex_module_del(dut: *mut ());
+    fn ex_module_eval(dut: *mut ());
+    fn ex_module_final(dut: *mut ());
+    fn ex_module_get_b(dut: *mut ()) -> u8;
+}
@@ -0,0 +1,22 @@
+impl ::ferrilator::Packed for Command {
+    const WIDTH: u32 = 31u32;
+    fn pack(&self) -> u128 {
+        ((self.op as u128 & 15u128) << 27u32) | ((self.addr as u128 & 4095u128) << 15u32)
+            | ((self.offset as u128 & 63u128) << 9u32)
+            | ((::ferrilator::Packed::pack(&self.header) & 255u128) << 1u32)
+            | (self.valid as u128 & 1u128)
+    }
+    fn unpack(bits: u128) -> Self {
+        Command {
+            op: ((bits >> 27u32) & 15u128) as u8,
+            addr: ((bits >> 15u32) & 4095u128) as u16,
+            offset: (((((bits >> 9u32) & 63u128) << 122u32) as i128) >> 122u32) as i8,
+            header: <Header as ::ferrilator::Packed>::unpack((bits >> 1u32) & 255u128),
+            valid: (bits & 1u128) != 0,
+        }
+    }
+}
+const _: () = assert!(
+    < Header as ::ferrilator::Packed > ::WIDTH == 8u32,
+    "field header of Command must be as wide as its type"
+);
@@ -0,0 +1,269 @@
+pub struct Example {
+    dut: *mut (),
+    time: u64,
+    watch_status: ::ferrilator::OnChange<<Status as ::ferrilator::PortType>::Read>,
+    watch_history: ::ferrilator::OnChange<
+        [<Status as ::ferrilator::PortType>::Read; 2usize],
+    >,
+}
+impl Example {
+    fn new() -> Self {
+        Self::with_config(&::ferrilator::Config::new())
+    }
+    fn with_threads(threads: u32) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+    }
+    fn with_seed(seed: u64) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+    }
+    fn with_args(args: &[&str]) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_args(args))
+    }
+    fn with_config(config: &::ferrilator::Config) -> Self {
+        let args = config.c_args();
+        let dut = unsafe { ex_module_new(config.threads(), args.argc(), args.argv()) };
+        if let (Some(seed), Some(verilator_seed)) = (
+            config.seed(),
+            config.verilator_seed(),
+        ) {
+            eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+        }
+        let mut dut = Self {
+            dut,
+            time: 0,
+            watch_status: ::ferrilator::OnChange::new(),
+            watch_history: ::ferrilator::OnChange::new(),
+        };
+        dut.eval();
+        dut
+    }
+    fn eval(&mut self) {
+        unsafe { ex_module_eval(self.dut) };
+        if self.watch_status.active() {
+            let now = self.get_status();
+            self.watch_status.update(now);
+        }
+        if self.watch_history.active() {
+            let now = self.get_history();
+            self.watch_history.update(now);
+        }
+    }
+    fn set_command(&mut self, value: Command) {
+        let value = ::ferrilator::Value::new(
+                ::ferrilator::PortType::to_bits(&value),
+                <Command as ::ferrilator::PortType>::WIDTH,
+            )
+            .bits();
+        let arr = [
+            (value & 0xffff_ffff) as u32,
+            ((value >> 32) & 0xffff_ffff) as u32,
+            ((value >> 64) & 0xffff_ffff) as u32,
+            ((value >> 96) & 0xffff_ffff) as u32,
+        ];
+        unsafe { ex_module_set_command(self.dut, &arr) };
+    }
+    fn get_status(&self) -> <Status as ::ferrilator::PortType>::Read {
+        <Status as ::ferrilator::PortType>::from_bits({
+            let mut arr = [0u32; 4];
+            unsafe { ex_module_get_status(self.dut, &mut arr) };
+            (arr[0] as u128) | ((arr[1] as u128) << 32) | ((arr[2] as u128) << 64)
+                | ((arr[3] as u128) << 96)
+        })
+    }
+    fn on_change_status(
+        &mut self,
+        callback: impl FnMut(
+            <Status as ::ferrilator::PortType>::Read,
+            <Status as ::ferrilator::PortType>::Read,
+        ) + Send + 'static,
+    ) {
+        let now = self.get_status();
+        self.watch_status.register(now, callback);
+    }
+    fn get_history_at(&self, index: usize) -> <Status as ::ferrilator::PortType>::Read {
+        assert!(index < 2usize, "index {index} out of range for history of length 2");
+        <Status as ::ferrilator::PortType>::from_bits({
+            let mut arr = [0u32; 4];
+            unsafe { ex_module_get_history(self.dut, index, &mut arr) };
+            (arr[0] as u128) | ((arr[1] as u128) << 32) | ((arr[2] as u128) << 64)
+                | ((arr[3] as u128) << 96)
+        })
+    }
+    fn get_history(&self) -> [<Status as ::ferrilator::PortType>::Read; 2usize] {
+        ::std::array::from_fn(|index| self.get_history_at(index))
+    }
+    fn on_change_history(
+        &mut self,
+        callback: impl FnMut(
+            [<Status as ::ferrilator::PortType>::Read; 2usize],
+            [<Status as ::ferrilator::PortType>::Read; 2usize],
+        ) + Send + 'static,
+    ) {
+        let now = self.get_history();
+        self.watch_history.register(now, callback);
+    }
+}
+impl ::ferrilator::Dut for Example {
+    fn eval(&mut self) {
+        Self::eval(self);
+    }
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn finish(&mut self) {
+        unsafe { ex_module_final(self.dut) };
+    }
+    fn ports(&self) -> &[::ferrilator::PortInfo] {
+        const PORTS: &[::ferrilator::PortInfo] = &[
+            ::ferrilator::PortInfo::new(
+                "command",
+                <Command as ::ferrilator::PortType>::WIDTH,
+                false,
+                true,
+                false,
+            ),
+            ::ferrilator::PortInfo::new(
+                "status",
+                <Status as ::ferrilator::PortType>::WIDTH,
+                false,
+                false,
+                true,
+            ),
+            ::ferrilator::PortInfo::new(
+                "history[0]",
+                <Status as ::ferrilator::PortType>::WIDTH,
+                false,
+                false,
+                true,
+            ),
+            ::ferrilator::PortInfo::new(
+                "history[1]",
+                <Status as ::ferrilator::PortType>::WIDTH,
+                false,
+                false,
+                true,
+            ),
+        ];
+        PORTS
+    }
+    fn set_port(
+        &mut self,
+        name: &str,
+        value: ::ferrilator::Value,
+    ) -> ::ferrilator::err::Result<()> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)?;
+        match name {
+            "command" => {
+                let value = value.bits();
+                let arr = [
+                    (value & 0xffff_ffff) as u32,
+                    ((value >> 32) & 0xffff_ffff) as u32,
+                    ((value >> 64) & 0xffff_ffff) as u32,
+                    ((value >> 96) & 0xffff_ffff) as u32,
+                ];
+                unsafe { ex_module_set_command(self.dut, &arr) };
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?;
+        Ok(
+            match name {
+                "command" => {
+                    ::ferrilator::Value::new(
+                        {
+                            let mut arr = [0u32; 4];
+                            unsafe { ex_module_get_command(self.dut, &mut arr) };
+                            (arr[0] as u128) | ((arr[1] as u128) << 32)
+                                | ((arr[2] as u128) << 64) | ((arr[3] as u128) << 96)
+                        },
+                        <Command as ::ferrilator::PortType>::WIDTH,
+                    )
+                }
+                "status" => {
+                    ::ferrilator::Value::new(
+                        {
+                            let mut arr = [0u32; 4];
+                            unsafe { ex_module_get_status(self.dut, &mut arr) };
+                            (arr[0] as u128) | ((arr[1] as u128) << 32)
+                                | ((arr[2] as u128) << 64) | ((arr[3] as u128) << 96)
+                        },
+                        <Status as ::ferrilator::PortType>::WIDTH,
+                    )
+                }
+                "history[0]" => {
+                    let index = 0usize;
+                    ::ferrilator::Value::new(
+                        {
+                            let mut arr = [0u32; 4];
+                            unsafe { ex_module_get_history(self.dut, index, &mut arr) };
+                            (arr[0] as u128) | ((arr[1] as u128) << 32)
+                                | ((arr[2] as u128) << 64) | ((arr[3] as u128) << 96)
+                        },
+                        <Status as ::ferrilator::PortType>::WIDTH,
+                    )
+                }
+                "history[1]" => {
+                    let index = 1usize;
+                    ::ferrilator::Value::new(
+                        {
+                            let mut arr = [0u32; 4];
+                            unsafe { ex_module_get_history(self.dut, index, &mut arr) };
+                            (arr[0] as u128) | ((arr[1] as u128) << 32)
+                                | ((arr[2] as u128) << 64) | ((arr[3] as u128) << 96)
+                        },
+                        <Status as ::ferrilator::PortType>::WIDTH,
+                    )
+                }
+                _ => unreachable!(),
+            },
+        )
+    }
+}
+#[cfg(not(ferrilator_global_context))]
+unsafe impl Send for Example {}
+#[cfg(not(ferrilator_global_context))]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<::ferrilator::OnChange<<Status as ::ferrilator::PortType>::Read>>();
+    assert_send::<
+        ::ferrilator::OnChange<[<Status as ::ferrilator::PortType>::Read; 2usize]>,
+    >();
+};
+const _: () = assert!(
+    ::ferrilator::PortInfo::width_matches(< Command as ::ferrilator::PortType > ::WIDTH,
+    option_env!("FERRILATOR_WIDTH_ex_module_command"),),
+    "the PortType::WIDTH of command is not the width of the port"
+);
+const _: () = assert!(
+    ::ferrilator::PortInfo::width_matches(< Status as ::ferrilator::PortType > ::WIDTH,
+    option_env!("FERRILATOR_WIDTH_ex_module_status"),),
+    "the PortType::WIDTH of status is not the width of the port"
+);
+const _: () = assert!(
+    ::ferrilator::PortInfo::width_matches(< Status as ::ferrilator::PortType > ::WIDTH,
+    option_env!("FERRILATOR_WIDTH_ex_module_history"),),
+    "the PortType::WIDTH of history is not the width of the port"
+);
+impl Drop for Example {
+    fn drop(&mut self) {
+        unsafe { ex_module_del(self.dut) };
+    }
+}
+#[link(name = "Vex_module")]
+unsafe extern "C" {
+    fn ex_module_new(
+        threads: u32,
+        argc: i32,
+        argv: *const *const ::std::ffi::c_char,
+    ) -> *mut ();
+    fn ex_module_del(dut: *mut ());
+    fn ex_module_eval(dut: *mut ());
+    fn ex_module_final(dut: *mut ());
+    fn ex_module_set_command(dut: *mut (), blocks: &[u32; 4]);
+    fn ex_module_get_command(dut: *mut (), blocks: &mut [u32]);
+    fn ex_module_get_status(dut: *mut (), blocks: &mut [u32]);
+    fn ex_module_get_history(dut: *mut (), index: usize, blocks: &mut [u32]);
+}
@@ -0,0 +1,260 @@
+pub struct Example {
+    dut: *mut (),
+    time: u64,
+    clocks: ::ferrilator::Scheduler,
+    history: Option<::ferrilator::History>,
+    watch_b: ::ferrilator::OnChange<u8>,
+}
+impl Example {
+    fn new() -> Self {
+        Self::with_config(&::ferrilator::Config::new())
+    }
+    fn with_threads(threads: u32) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+    }
+    fn with_seed(seed: u64) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+    }
+    fn with_args(args: &[&str]) -> Self {
+        Self::with_config(&::ferrilator::Config::new().with_args(args))
+    }
+    fn with_config(config: &::ferrilator::Config) -> Self {
+        let args = config.c_args();
+        let dut = unsafe { ex_module_new(config.threads(), args.argc(), args.argv()) };
+        if let (Some(seed), Some(verilator_seed)) = (
+            config.seed(),
+            config.verilator_seed(),
+        ) {
+            eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+        }
+        let mut dut = Self {
+            dut,
+            time: 0,
+            clocks: ::ferrilator::Scheduler::new(&[(10000u64, false)]),
+            history: None,
+            watch_b: ::ferrilator::OnChange::new(),
+        };
+        dut.eval();
+        dut
+    }
+    fn eval(&mut self) {
+        unsafe { ex_module_eval(self.dut) };
+        if self.watch_b.active() {
+            let now = self.get_b();
+            self.watch_b.update(now);
+        }
+    }
+    fn step(&mut self) {
+        let (time, edges) = self.clocks.advance();
+        self.time = time;
+        for (index, level) in edges {
+            match index {
+                0usize => self.set_clk(level),
+                _ => unreachable!(),
+            }
+        }
+        self.eval();
+    }
+    fn run_for(&mut self, ns: u64) {
+        let end = self.time + ns * 1000;
+        while self.clocks.next_edge().is_some_and(|next| next <= end) {
+            self.step();
+        }
+        self.time = end;
+    }
+    fn posedge(&mut self) {
+        self.set_clk(true);
+        self.time += 5000u64;
+        self.clocks.drive(0, true, self.time);
+        self.eval();
+    }
+    fn negedge(&mut self) {
+        self.set_clk(false);
+        self.time += 5000u64;
+        self.clocks.drive(0, false, self.time);
+        self.eval();
+    }
+    fn tick(&mut self) {
+        self.posedge();
+        self.negedge();
+        if let Some(mut history) = self.history.take() {
+            history.record(self);
+            self.history = Some(history);
+        }
+    }
+    fn tick_n(&mut self, n: usize) {
+        for _ in 0..n {
+            self.tick();
+        }
+    }
+    fn record_history(&mut self, cycles: usize) {
+        self.history = Some(::ferrilator::History::new(cycles));
+    }
+    fn history(&self) -> Option<&::ferrilator::History> {
+        self.history.as_ref()
+    }
+    fn save(
+        &self,
+        path: impl AsRef<::std::path::Path>,
+    ) -> ::ferrilator::err::Result<()> {
+        let mut words = vec![self.time];
+        words.extend(self.clocks.save());
+        ::ferrilator::save::write(
+            path.as_ref(),
+            &words,
+            |path, words, len| unsafe { ex_module_save(self.dut, path, words, len) },
+        )
+    }
+    fn restore(
+        &mut self,
+        path: impl AsRef<::std::path::Path>,
+    ) -> ::ferrilator::err::Result<()> {
+        let mut words = vec![0; 4usize];
+        let dut = self.dut;
+        ::ferrilator::save::read(
+            path.as_ref(),
+            &mut words,
+            |path, words, len| unsafe { ex_module_restore(dut, path, words, len) },
+        )?;
+        self.time = words[0];
+        self.clocks.restore(&words[1..]);
+        Ok(())
+    }
+    fn snapshot(&self) -> ::ferrilator::err::Result<::ferrilator::save::Snapshot> {
+        ::ferrilator::save::Snapshot::capture(|path| self.save(path))
+    }
+    fn restore_snapshot(
+        &mut self,
+        snapshot: &::ferrilator::save::Snapshot,
+    ) -> ::ferrilator::err::Result<()> {
+        snapshot.apply(|path| self.restore(path))
+    }
+    fn set_clk(&mut self, value: bool) {
+        unsafe { ex_module_set_clk(self.dut, value) };
+    }
+    fn get_b(&self) -> u8 {
+        unsafe { ex_module_get_b(self.dut) }
+    }
+    fn on_change_b(&mut self, callback: impl FnMut(u8, u8) + Send + 'static) {
+        let now = self.get_b();
+        self.watch_b.register(now, callback);
+    }
+}
+impl ::ferrilator::Dut for Example {
+    fn eval(&mut self) {
+        Self::eval(self);
+    }
+    fn time(&self) -> u64 {
+        self.time
+    }
+    fn cycle(&self) -> u64 {
+        self.clocks.cycles(0)
+    }
+    fn finish(&mut self) {
+        unsafe { ex_module_final(self.dut) };
+    }
+    fn ports(&self) -> &[::ferrilator::PortInfo] {
+        const PORTS: &[::ferrilator::PortInfo] = &[
+            ::ferrilator::PortInfo::new("clk", 1u32, false, true, false),
+            ::ferrilator::PortInfo::new("b", 8u32, false, false, true),
+        ];
+        PORTS
+    }
+    fn set_port(
+        &mut self,
+        name: &str,
+        value: ::ferrilator::Value,
+    ) -> ::ferrilator::err::Result<()> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)?;
+        match name {
+            "clk" => self.set_clk(value.bits() != 0),
+            _ => unreachable!(),
+        }
+        Ok(())
+    }
+    fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+        ::ferrilator::PortInfo::find(self.ports(), name)?;
+        Ok(
+            match name {
+                "clk" => {
+                    ::ferrilator::Value::from(unsafe { ex_module_get_clk(self.dut) })
+                }
+                "b" => ::ferrilator::Value::from(unsafe { ex_module_get_b(self.dut) }),
+                _ => unreachable!(),
+            },
+        )
+    }
+}
+impl ::ferrilator::Scheduled for Example {
+    fn clocks(&self) -> &[&'static str] {
+        &["clk"]
+    }
+    fn step(&mut self) {
+        Self::step(self);
+    }
+    fn run_for(&mut self, ns: u64) {
+        Self::run_for(self, ns);
+    }
+}
+impl ::ferrilator::Clocked for Example {
+    fn posedge(&mut self) {
+        Self::posedge(self);
+    }
+    fn negedge(&mut self) {
+        Self::negedge(self);
+    }
+    fn tick(&mut self) {
+        Self::tick(self);
+    }
+}
+impl ::ferrilator::Savable for Example {
+    fn save(&self, path: &::std::path::Path) -> ::ferrilator::err::Result<()> {
+        Self::save(self, path)
+    }
+    fn restore(&mut self, path: &::std::path::Path) -> ::ferrilator::err::Result<()> {
+        Self::restore(self, path)
+    }
+}
+#[cfg(not(ferrilator_global_context))]
+unsafe impl Send for Example {}
+#[cfg(not(ferrilator_global_context))]
+const _: () = {
+    const fn assert_send<T: Send>() {}
+    assert_send::<::ferrilator::Scheduler>();
+    assert_send::<Option<::ferrilator::History>>();
+    assert_send::<::ferrilator::OnChange<u8>>();
+};
+impl Drop for Example {
+    fn drop(&mut self) {
+        if let Some(history) = &self.history {
+            history.print_if_panicking();
+        }
+        unsafe { ex_module_del(self.dut) };
+    }
+}
+#[link(name = "Vex_module")]
+unsafe extern "C" {
+    fn ex_module_new(
+        threads: u32,
+        argc: i32,
+        argv: *const *const ::std::ffi::c_char,
+    ) -> *mut ();
+    fn ex_module_del(dut: *mut ());
+    fn ex_module_eval(dut: *mut ());
+    fn ex_module_final(dut: *mut ());
+    fn ex_module_save(
+        dut: *mut (),
+        path: *const ::std::ffi::c_char,
+        words: *const u64,
+        len: usize,
+    ) -> bool;
+    fn ex_module_restore(
+        dut: *mut (),
+        path: *const ::std::ffi::c_char,
+        words: *mut u64,
+        len: usize,
+    ) -> bool;
+    fn ex_module_set_clk(dut: *mut (), value: bool);
+    fn ex_module_get_clk(dut: *mut ()) -> bool;
+    fn ex_module_get_b(dut: *mut ()) -> u8;
+}
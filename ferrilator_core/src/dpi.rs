@@ -0,0 +1,815 @@
+use crate::Module;
+use crate::Options;
+use crate::as_tokens;
+use crate::err;
+use proc_macro2::Ident;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::quote;
+use std::collections::HashSet;
+use syn::FnArg;
+use syn::ItemFn;
+use syn::ReturnType;
+use syn::Type;
+use syn::parse2;
+
+/// Generate an `extern "C"` function named after `item`, with the DPI ABI,
+/// that converts its arguments and calls `item`. This lets the Rust function
+/// implement an `import "DPI-C"` function of the design.
+pub fn dpi_export_attribute(attr: TokenStream, item: TokenStream) -> err::Result<TokenStream> {
+    if !attr.is_empty() {
+        return err::input!("unexpected attr value: {attr}");
+    }
+    let mut item: ItemFn = parse2(item)?;
+    // the widths of packed vectors are only for the generated function
+    let mut widths = vec![];
+    for input in item.sig.inputs.iter_mut() {
+        if let FnArg::Typed(input) = input {
+            widths.push(take_bits(&mut input.attrs)?);
+        }
+    }
+    let sig = &item.sig;
+    let name = sig.ident.to_string();
+    if sig.asyncness.is_some()
+        || sig.unsafety.is_some()
+        || sig.abi.is_some()
+        || sig.variadic.is_some()
+        || !sig.generics.params.is_empty()
+    {
+        return err::input!("DPI function {name} must be a plain function without generics");
+    }
+
+    let ident = &sig.ident;
+    let mut params = vec![];
+    let mut before = vec![];
+    let mut args = vec![];
+    let mut after = vec![];
+    for (index, input) in sig.inputs.iter().enumerate() {
+        let FnArg::Typed(input) = input else {
+            return err::input!("DPI function {name} cannot take self");
+        };
+        let arg = Ident::new(&format!("arg{index}"), Span::call_site());
+        let dpi_arg = DpiArg::from_type(&input.ty, widths[index])?;
+        params.push(dpi_arg.param(&arg));
+        let (pre, value, post) = dpi_arg.convert(&arg);
+        before.push(pre);
+        args.push(value);
+        after.push(post);
+    }
+
+    let (ret, result) = match &sig.output {
+        ReturnType::Default => (quote! {}, quote! {}),
+        ReturnType::Type(_, ty) => match Scalar::from_type(ty) {
+            Some(Scalar::Bool) => (quote! { -> u8 }, quote! { result as u8 }),
+            Some(Scalar::Plain(ty)) => {
+                let ty = Ident::new(ty, Span::call_site());
+                (quote! { -> #ty }, quote! { result })
+            }
+            _ => {
+                return err::input!(
+                    "DPI function {name} cannot return {}, only scalars",
+                    as_tokens(ty)
+                );
+            }
+        },
+    };
+
+    let call = if result.is_empty() {
+        quote! { #ident(#(#args),*); }
+    } else {
+        quote! { let result = #ident(#(#args),*); }
+    };
+
+    Ok(quote! {
+        #item
+
+        const _: () = {
+            #[unsafe(export_name = #name)]
+            unsafe extern "C" fn dpi(#(#params),*) #ret {
+                #(#before)*
+                #call
+                #(#after)*
+                #result
+            }
+        };
+    })
+}
+
+/// Generate methods on the struct of `module` that call the
+/// `export "DPI-C"` functions declared in `header`, the `V{module}__Dpi.h`
+/// written by Verilator. Each method sets the scope to the top module of the
+/// design before the call, so only functions exported from the top module
+/// can be called. Returns the code and the names of the exports that were
+/// skipped, with the reason, because their arguments have no Rust
+/// equivalent or their names are taken by the generated methods.
+pub fn dpi_exports(
+    module: &Module,
+    header: &str,
+) -> err::Result<(TokenStream, Vec<(String, String)>)> {
+    let ident = &module.ident;
+    let mod_dpi_scope = Ident::new(&format!("{}_dpi_scope", module.name), Span::call_site());
+    let taken = method_names(module)?;
+
+    let mut fns = vec![];
+    let mut skipped = vec![];
+    for export in Export::parse_header(header) {
+        if taken.contains(&export.name) {
+            let reason = format!("{} is the name of a generated method", export.name);
+            skipped.push((export.name, reason));
+            continue;
+        }
+        match export.method(&mod_dpi_scope, module.options()) {
+            Ok(method) => fns.push(method),
+            Err(reason) => skipped.push((export.name, reason)),
+        }
+    }
+
+    // every export gets a method, whether the tests call it or not
+    let tokens = quote! {
+        #[allow(dead_code)]
+        impl #ident {
+            #(#fns)*
+        }
+    };
+    Ok((tokens, skipped))
+}
+
+/// The default methods of the ferrilator traits the generated structs
+/// implement, which are not in the generated code. The ferrilator tests
+/// check that every default method is listed.
+#[doc(hidden)]
+pub const TRAIT_METHODS: &[&str] = &["cycle", "tick_n", "snapshot", "restore_snapshot"];
+
+/// The names of the methods generated for `module`, including those the
+/// ferrilator traits provide, which an export method would shadow.
+fn method_names(module: &Module) -> err::Result<HashSet<String>> {
+    let mut names: HashSet<String> = TRAIT_METHODS.iter().map(|name| name.to_string()).collect();
+    let code: syn::File = parse2(crate::module_code(module)?)?;
+    for item in code.items {
+        if let syn::Item::Impl(item) = item {
+            for item in item.items {
+                if let syn::ImplItem::Fn(item) = item {
+                    names.insert(item.sig.ident.to_string());
+                }
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// A prototype from the `// DPI EXPORTS` section of a `__Dpi.h` header,
+/// like `extern void preload(int index, const svBitVecVal* value);`.
+#[derive(Debug, PartialEq)]
+struct Export {
+    name: String,
+    ret: String,
+    args: Vec<(String, String)>,
+}
+
+impl Export {
+    fn parse_header(header: &str) -> Vec<Export> {
+        let mut exports = vec![];
+        let mut in_exports = false;
+        for line in header.lines() {
+            let line = line.trim();
+            if line.starts_with("// DPI EXPORTS") {
+                in_exports = true;
+            } else if line.starts_with("// DPI IMPORTS") {
+                in_exports = false;
+            } else if in_exports && let Some(export) = Export::parse(line) {
+                exports.push(export);
+            }
+        }
+        exports
+    }
+
+    fn parse(line: &str) -> Option<Export> {
+        let line = line.strip_prefix("extern ")?.strip_suffix(';')?;
+        let (head, args) = line.strip_suffix(')')?.split_once('(')?;
+        let (ret, name) = split_declaration(head)?;
+        let args = match args.trim() {
+            "" | "void" => vec![],
+            args => args
+                .split(',')
+                .map(split_declaration)
+                .collect::<Option<_>>()?,
+        };
+        Some(Export { name, ret, args })
+    }
+
+    /// The Rust method calling the export, or why an argument or the return
+    /// type is not supported. The widths of packed vectors come from the
+    /// `dpi` option.
+    fn method(&self, mod_dpi_scope: &Ident, options: &Options) -> Result<TokenStream, String> {
+        let name = syn::parse_str::<Ident>(&self.name)
+            .map_err(|_| format!("{} is a Rust keyword", self.name))?;
+        let mut params = vec![];
+        let mut extern_params = vec![];
+        let mut before = vec![];
+        let mut args = vec![];
+        let mut after = vec![];
+        for (index, (ty, arg_name)) in self.args.iter().enumerate() {
+            // the generated locals all start with `__`, and an argument
+            // named like the export would hide the function being called
+            let arg = match syn::parse_str::<Ident>(arg_name) {
+                Ok(arg) if !arg_name.starts_with("__") && *arg_name != self.name => arg,
+                _ => Ident::new(&format!("__arg{index}"), Span::call_site()),
+            };
+            let bits = || {
+                options.dpi_bits(&self.name, arg_name).ok_or_else(|| {
+                    format!(
+                        "{ty} {arg_name} is a packed vector, whose width the header does not give, set it with dpi({}({arg_name} = n))",
+                        self.name
+                    )
+                })
+            };
+            let dpi_arg = match DpiArg::from_c_type(ty).ok_or_else(|| unsupported_c_type(ty))? {
+                DpiArg::Scalar(Scalar::Wide(_)) => DpiArg::Scalar(Scalar::Wide(bits()?)),
+                DpiArg::Output(Scalar::Wide(_)) => DpiArg::Output(Scalar::Wide(bits()?)),
+                dpi_arg => dpi_arg,
+            };
+            params.push(dpi_arg.rust_param(&arg));
+            extern_params.push(dpi_arg.param(&arg));
+            let value = Ident::new(&format!("__c{index}"), Span::call_site());
+            let (pre, value, post) = dpi_arg.convert_to_c(&arg, &value);
+            before.push(pre);
+            args.push(value);
+            after.push(post);
+        }
+
+        let (ret, extern_ret, result) = match self.ret.as_str() {
+            "void" => (quote! {}, quote! {}, quote! {}),
+            ty => match DpiArg::from_c_type(ty).ok_or_else(|| unsupported_c_type(ty))? {
+                DpiArg::Scalar(Scalar::Bool) => {
+                    (quote! { -> bool }, quote! { -> u8 }, quote! { == 1 })
+                }
+                DpiArg::Scalar(scalar @ Scalar::Plain(_)) => {
+                    let ty = scalar.abi_type();
+                    (quote! { -> #ty }, quote! { -> #ty }, quote! {})
+                }
+                _ => return Err(format!("{ty} cannot be returned")),
+            },
+        };
+
+        let call = quote! { unsafe { #name(#(#args),*) } };
+        let call = if after.iter().all(TokenStream::is_empty) && result.is_empty() {
+            quote! { #(#before)* #call }
+        } else if ret.is_empty() {
+            quote! { #(#before)* #call; #(#after)* }
+        } else {
+            quote! { #(#before)* let __result = #call; #(#after)* __result #result }
+        };
+
+        Ok(quote! {
+            fn #name(&mut self, #(#params),*) #ret {
+                unsafe extern "C" {
+                    fn #name(#(#extern_params),*) #extern_ret;
+                }
+                unsafe { #mod_dpi_scope(self.dut) };
+                #call
+            }
+        })
+    }
+}
+
+/// Why an argument of the C type `ty` cannot be passed from Rust.
+fn unsupported_c_type(ty: &str) -> String {
+    if ty.contains("svLogicVecVal") {
+        format!("{ty} is a 4-state vector, whose width the header does not give")
+    } else {
+        format!("{ty} has no Rust equivalent")
+    }
+}
+
+/// Remove a `#[bits(n)]` attribute and return `n`.
+fn take_bits(attrs: &mut Vec<syn::Attribute>) -> err::Result<Option<u32>> {
+    let Some(index) = attrs.iter().position(|attr| attr.path().is_ident("bits")) else {
+        return Ok(None);
+    };
+    let attr = attrs.remove(index);
+    let bits: u32 = attr.parse_args::<syn::LitInt>()?.base10_parse()?;
+    if bits == 0 || bits > 128 {
+        return err::input!("#[bits({bits})] is out of range, packed vectors have 1 to 128 bits");
+    }
+    Ok(Some(bits))
+}
+
+/// Split a C declaration like `const svBitVecVal* value` into its type
+/// and name.
+fn split_declaration(declaration: &str) -> Option<(String, String)> {
+    let declaration = declaration.trim();
+    let start = declaration
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .map_or(0, |index| index + 1);
+    let (ty, name) = declaration.split_at(start);
+    let ty = ty.trim().replace(" *", "*");
+    if ty.is_empty() || name.is_empty() {
+        return None;
+    }
+    Some((ty, name.to_string()))
+}
+
+/// The Rust types that can be passed to and from DPI functions.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Scalar {
+    /// `bit` and `logic`, passed as `svBit` or `svLogic`. X and Z read as
+    /// false.
+    Bool,
+    /// `byte`, `shortint`, `int`, `longint`, `real` and `shortreal`, which
+    /// have the same representation in C and Rust.
+    Plain(&'static str),
+    /// Packed vectors of the given number of bits, at most 128, passed as
+    /// `svBitVecVal*`.
+    Wide(u32),
+    /// 4-state packed vectors, `Logic<N>`, passed as `svLogicVecVal*`.
+    Logic(u32),
+}
+
+impl Scalar {
+    fn from_type(ty: &Type) -> Option<Scalar> {
+        let Type::Path(path) = ty else {
+            return None;
+        };
+        if let Some(width) = logic_width(&path.path) {
+            return Some(Scalar::Logic(width));
+        }
+        let ident = path.path.get_ident()?.to_string();
+        match ident.as_str() {
+            "bool" => Some(Scalar::Bool),
+            "u8" => Some(Scalar::Plain("u8")),
+            "i8" => Some(Scalar::Plain("i8")),
+            "u16" => Some(Scalar::Plain("u16")),
+            "i16" => Some(Scalar::Plain("i16")),
+            "u32" => Some(Scalar::Plain("u32")),
+            "i32" => Some(Scalar::Plain("i32")),
+            "u64" => Some(Scalar::Plain("u64")),
+            "i64" => Some(Scalar::Plain("i64")),
+            "f32" => Some(Scalar::Plain("f32")),
+            "f64" => Some(Scalar::Plain("f64")),
+            // the width is set from the argument's attribute
+            "u128" => Some(Scalar::Wide(0)),
+            _ => None,
+        }
+    }
+
+    /// The scalar passed as the C type `ty` in a `__Dpi.h` header.
+    fn from_c_type(ty: &str) -> Option<Scalar> {
+        match ty {
+            "svBit" | "svLogic" => Some(Scalar::Bool),
+            "unsigned char" => Some(Scalar::Plain("u8")),
+            "char" => Some(Scalar::Plain("i8")),
+            "unsigned short" => Some(Scalar::Plain("u16")),
+            "short" => Some(Scalar::Plain("i16")),
+            "unsigned int" => Some(Scalar::Plain("u32")),
+            "int" => Some(Scalar::Plain("i32")),
+            "unsigned long long" => Some(Scalar::Plain("u64")),
+            "long long" => Some(Scalar::Plain("i64")),
+            "float" => Some(Scalar::Plain("f32")),
+            "double" => Some(Scalar::Plain("f64")),
+            _ => None,
+        }
+    }
+
+    fn abi_type(&self) -> TokenStream {
+        match self {
+            Scalar::Bool => quote! { u8 },
+            Scalar::Plain(ty) => {
+                let ty = Ident::new(ty, Span::call_site());
+                quote! { #ty }
+            }
+            Scalar::Wide(_) => quote! { *const u32 },
+            Scalar::Logic(_) => quote! { *const ::ferrilator::dpi::LogicVecVal },
+        }
+    }
+
+    fn rust_type(&self) -> TokenStream {
+        match self {
+            Scalar::Bool => quote! { bool },
+            Scalar::Plain(_) => self.abi_type(),
+            Scalar::Wide(_) => quote! { u128 },
+            Scalar::Logic(width) => {
+                let width = proc_macro2::Literal::u32_unsuffixed(*width);
+                quote! { ::ferrilator::Logic<#width> }
+            }
+        }
+    }
+}
+
+/// The width of a `Logic<N>` type, `N` must be a literal.
+fn logic_width(path: &syn::Path) -> Option<u32> {
+    let segment = path.segments.last()?;
+    if segment.ident != "Logic" {
+        return None;
+    }
+    let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        syn::GenericArgument::Const(syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(width),
+            ..
+        })) => width.base10_parse().ok(),
+        _ => None,
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum DpiArg {
+    /// An `input` argument.
+    Scalar(Scalar),
+    /// An `output` or `inout` argument, `&mut T`.
+    Output(Scalar),
+    /// An `input` `string`, `&str`.
+    Str,
+    /// An `input` open array like `int values[]`, `&[T]`.
+    OpenArray(Scalar),
+    /// An `output` or `inout` open array, `&mut [T]`.
+    OpenArrayMut(Scalar),
+}
+
+impl DpiArg {
+    /// The argument of type `ty`, with the width from `#[bits(n)]` if it
+    /// had one.
+    fn from_type(ty: &Type, bits: Option<u32>) -> err::Result<DpiArg> {
+        let unsupported = || {
+            err::input!(
+                "DPI arguments must be bool, integers, floats, u128, Logic<N>, &str, &mut or slices of these, found {}",
+                as_tokens(ty)
+            )
+        };
+        let arg = match ty {
+            Type::Reference(reference) => match (&*reference.elem, reference.mutability) {
+                (Type::Path(path), None) if path.path.is_ident("str") => DpiArg::Str,
+                (Type::Slice(slice), mutability) => {
+                    let Some(scalar) = Scalar::from_type(&slice.elem) else {
+                        return unsupported();
+                    };
+                    if !matches!(scalar, Scalar::Plain(_)) {
+                        return unsupported();
+                    }
+                    if mutability.is_some() {
+                        DpiArg::OpenArrayMut(scalar)
+                    } else {
+                        DpiArg::OpenArray(scalar)
+                    }
+                }
+                (elem, Some(_)) => match Scalar::from_type(elem) {
+                    Some(scalar) => DpiArg::Output(scalar),
+                    None => return unsupported(),
+                },
+                _ => return unsupported(),
+            },
+            _ => match Scalar::from_type(ty) {
+                Some(scalar) => DpiArg::Scalar(scalar),
+                None => return unsupported(),
+            },
+        };
+        let arg = match (arg, bits) {
+            (DpiArg::Scalar(Scalar::Wide(_)), Some(bits)) => DpiArg::Scalar(Scalar::Wide(bits)),
+            (DpiArg::Output(Scalar::Wide(_)), Some(bits)) => DpiArg::Output(Scalar::Wide(bits)),
+            (DpiArg::Scalar(Scalar::Wide(_)) | DpiArg::Output(Scalar::Wide(_)), None) => {
+                return err::input!(
+                    "u128 DPI arguments need #[bits(n)] with the width of the packed vector"
+                );
+            }
+            (arg, None) => arg,
+            (_, Some(_)) => {
+                return err::input!(
+                    "#[bits(n)] is only for u128 DPI arguments, found {}",
+                    as_tokens(ty)
+                );
+            }
+        };
+        Ok(arg)
+    }
+
+    /// The argument declared as the C type `ty` in a `__Dpi.h` header. Open
+    /// arrays cannot be passed from Rust.
+    fn from_c_type(ty: &str) -> Option<DpiArg> {
+        let arg = match ty {
+            "const char*" => DpiArg::Str,
+            // the header does not give the width, see `Options::dpi_bits`
+            "const svBitVecVal*" => DpiArg::Scalar(Scalar::Wide(0)),
+            "svBitVecVal*" => DpiArg::Output(Scalar::Wide(0)),
+            ty => match ty.strip_suffix('*') {
+                Some(ty) => DpiArg::Output(Scalar::from_c_type(ty)?),
+                None => DpiArg::Scalar(Scalar::from_c_type(ty)?),
+            },
+        };
+        Some(arg)
+    }
+
+    /// The parameter of the generated method calling an export.
+    fn rust_param(&self, arg: &Ident) -> TokenStream {
+        match self {
+            DpiArg::Scalar(scalar) => {
+                let ty = scalar.rust_type();
+                quote! { #arg: #ty }
+            }
+            DpiArg::Output(scalar) => {
+                let ty = scalar.rust_type();
+                quote! { #arg: &mut #ty }
+            }
+            DpiArg::Str => quote! { #arg: &str },
+            DpiArg::OpenArray(_) | DpiArg::OpenArrayMut(_) => unreachable!(),
+        }
+    }
+
+    /// Statements run before calling an export, the value passed to it and
+    /// statements that copy outputs back afterwards, with `value` holding
+    /// the converted argument.
+    fn convert_to_c(&self, arg: &Ident, value: &Ident) -> (TokenStream, TokenStream, TokenStream) {
+        match self {
+            DpiArg::Scalar(Scalar::Bool) => (quote! {}, quote! { #arg as u8 }, quote! {}),
+            DpiArg::Scalar(Scalar::Plain(_)) => (quote! {}, quote! { #arg }, quote! {}),
+            DpiArg::Scalar(Scalar::Wide(bits)) => (
+                quote! {
+                    let mut #value = [0u32; 4];
+                    unsafe { ::ferrilator::dpi::write_wide(#value.as_mut_ptr(), #bits, #arg) };
+                },
+                quote! { #value.as_ptr() },
+                quote! {},
+            ),
+            DpiArg::Output(Scalar::Bool) => (
+                quote! { let mut #value = *#arg as u8; },
+                quote! { &mut #value },
+                quote! { *#arg = #value == 1; },
+            ),
+            DpiArg::Output(Scalar::Plain(_)) => (quote! {}, quote! { #arg }, quote! {}),
+            DpiArg::Output(Scalar::Wide(bits)) => (
+                quote! {
+                    let mut #value = [0u32; 4];
+                    unsafe { ::ferrilator::dpi::write_wide(#value.as_mut_ptr(), #bits, *#arg) };
+                },
+                quote! { #value.as_mut_ptr() },
+                quote! { *#arg = unsafe { ::ferrilator::dpi::read_wide(#value.as_ptr(), #bits) }; },
+            ),
+            DpiArg::Str => (
+                quote! {
+                    let #value = ::std::ffi::CString::new(#arg)
+                        .expect("DPI string arguments cannot contain nul bytes");
+                },
+                quote! { #value.as_ptr() },
+                quote! {},
+            ),
+            // not read from headers
+            DpiArg::Scalar(Scalar::Logic(_))
+            | DpiArg::Output(Scalar::Logic(_))
+            | DpiArg::OpenArray(_)
+            | DpiArg::OpenArrayMut(_) => unreachable!(),
+        }
+    }
+
+    /// The parameter of the `extern "C"` function.
+    fn param(&self, arg: &Ident) -> TokenStream {
+        match self {
+            DpiArg::Scalar(scalar) => {
+                let ty = scalar.abi_type();
+                quote! { #arg: #ty }
+            }
+            DpiArg::Output(Scalar::Wide(_)) => quote! { #arg: *mut u32 },
+            DpiArg::Output(Scalar::Logic(_)) => {
+                quote! { #arg: *mut ::ferrilator::dpi::LogicVecVal }
+            }
+            DpiArg::Output(scalar) => {
+                let ty = scalar.abi_type();
+                quote! { #arg: *mut #ty }
+            }
+            DpiArg::Str => quote! { #arg: *const ::std::ffi::c_char },
+            DpiArg::OpenArray(_) | DpiArg::OpenArrayMut(_) => {
+                quote! { #arg: ::ferrilator::dpi::OpenArrayHandle }
+            }
+        }
+    }
+
+    /// Statements run before the call, the value passed to the Rust
+    /// function and statements that copy outputs back afterwards.
+    fn convert(&self, arg: &Ident) -> (TokenStream, TokenStream, TokenStream) {
+        let value = Ident::new(&format!("{arg}_value"), Span::call_site());
+        match self {
+            DpiArg::Scalar(Scalar::Bool) => (quote! {}, quote! { #arg == 1 }, quote! {}),
+            DpiArg::Scalar(Scalar::Plain(_)) => (quote! {}, quote! { #arg }, quote! {}),
+            DpiArg::Scalar(Scalar::Wide(bits)) => (
+                quote! {},
+                quote! { unsafe { ::ferrilator::dpi::read_wide(#arg, #bits) } },
+                quote! {},
+            ),
+            DpiArg::Scalar(Scalar::Logic(_)) => (
+                quote! {},
+                quote! { unsafe { ::ferrilator::dpi::read_logic(#arg) } },
+                quote! {},
+            ),
+            DpiArg::Output(Scalar::Bool) => (
+                quote! { let mut #value = unsafe { *#arg == 1 }; },
+                quote! { &mut #value },
+                quote! { unsafe { *#arg = #value as u8 }; },
+            ),
+            DpiArg::Output(Scalar::Plain(_)) => {
+                (quote! {}, quote! { unsafe { &mut *#arg } }, quote! {})
+            }
+            DpiArg::Output(Scalar::Wide(bits)) => (
+                quote! { let mut #value = unsafe { ::ferrilator::dpi::read_wide(#arg, #bits) }; },
+                quote! { &mut #value },
+                quote! { unsafe { ::ferrilator::dpi::write_wide(#arg, #bits, #value) }; },
+            ),
+            DpiArg::Output(Scalar::Logic(_)) => (
+                quote! { let mut #value = unsafe { ::ferrilator::dpi::read_logic(#arg) }; },
+                quote! { &mut #value },
+                quote! { unsafe { ::ferrilator::dpi::write_logic(#arg, #value) }; },
+            ),
+            DpiArg::Str => (
+                quote! { let #value = unsafe { ::std::ffi::CStr::from_ptr(#arg) }.to_string_lossy(); },
+                quote! { &#value },
+                quote! {},
+            ),
+            DpiArg::OpenArray(scalar) => {
+                let ty = scalar.rust_type();
+                (
+                    quote! {},
+                    quote! { unsafe { ::ferrilator::dpi::open_array::<#ty>(#arg) } },
+                    quote! {},
+                )
+            }
+            DpiArg::OpenArrayMut(scalar) => {
+                let ty = scalar.rust_type();
+                (
+                    quote! {},
+                    quote! { unsafe { ::ferrilator::dpi::open_array_mut::<#ty>(#arg) } },
+                    quote! {},
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::snapshot;
+
+    #[test]
+    fn dpi_export() -> err::Result<()> {
+        let item = quote! {
+            fn ref_model(
+                a: u32,
+                flag: bool,
+                #[bits(96)] wide: u128,
+                name: &str,
+                values: &[i32],
+                results: &mut [u64],
+                total: &mut i64,
+                done: &mut bool,
+                #[bits(128)] big: &mut u128,
+                state: Logic<40>,
+                next: &mut ferrilator::Logic<3>,
+            ) -> u64 {
+                0
+            }
+        };
+
+        let output = dpi_export_attribute(quote! {}, item)?;
+
+        snapshot("dpi_export.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn dpi_export_void() -> err::Result<()> {
+        let item = quote! {
+            fn poke(addr: u32, data: u8) {}
+        };
+
+        let output = dpi_export_attribute(quote! {}, item)?;
+
+        snapshot("dpi_void.rs", output);
+        Ok(())
+    }
+
+    const HEADER: &str = r#"
+#include "svdpi.h"
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+    // DPI EXPORTS
+    // DPI export at src/hdl/ram.sv:12:34
+    extern void preload(int index, const svBitVecVal* value);
+    // DPI export at src/hdl/ram.sv:16:34
+    extern unsigned int peek(int index);
+    // DPI export at src/hdl/ram.sv:20:30
+    extern int dump(svBitVecVal* words, svBit* ok, long long *count);
+    // DPI export at src/hdl/ram.sv:24:34
+    extern svLogic named(const char* name, svLogic enable);
+    // DPI export at src/hdl/ram.sv:28:34
+    extern void handle(void* opaque);
+    // DPI export at src/hdl/ram.sv:32:34
+    extern void mask(const svLogicVecVal* bits);
+    // DPI export at src/hdl/ram.sv:36:34
+    extern void eval();
+    // DPI export at src/hdl/ram.sv:40:34
+    extern int checksum(int result, const svBitVecVal* c_result, int checksum, int __c0);
+    // DPI export at src/hdl/ram.sv:44:34
+    extern void fill(const svBitVecVal* pattern);
+
+    // DPI IMPORTS
+    // DPI import at src/hdl/ram.sv:8:34
+    extern void ref_model(int a);
+
+#ifdef __cplusplus
+}
+#endif
+"#;
+
+    #[test]
+    fn dpi_exports_header() -> err::Result<()> {
+        let attr =
+            quote! { ram, dpi(preload(value = 40), dump(words = 128), checksum(c_result = 8)) };
+        let item = quote! {
+            struct Ram {
+                #[input]
+                addr: u8,
+            }
+        };
+        let module = Module::from_attribute(attr, item)?;
+
+        let (output, skipped) = dpi_exports(&module, HEADER)?;
+
+        assert_eq!(
+            vec![
+                (
+                    "handle".to_string(),
+                    "void* has no Rust equivalent".to_string()
+                ),
+                (
+                    "mask".to_string(),
+                    "const svLogicVecVal* is a 4-state vector, whose width the header does not give"
+                        .to_string()
+                ),
+                (
+                    "eval".to_string(),
+                    "eval is the name of a generated method".to_string()
+                ),
+                (
+                    "fill".to_string(),
+                    "const svBitVecVal* pattern is a packed vector, whose width the header does not give, set it with dpi(fill(pattern = n))"
+                        .to_string()
+                ),
+            ],
+            skipped
+        );
+        snapshot("dpi_exports.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_exports() {
+        assert_eq!(
+            Some(Export {
+                name: "dump".into(),
+                ret: "int".into(),
+                args: vec![
+                    ("svBitVecVal*".into(), "words".into()),
+                    ("long long*".into(), "count".into()),
+                ],
+            }),
+            Export::parse("extern int dump(svBitVecVal* words, long long *count);")
+        );
+        assert_eq!(
+            Some(Export {
+                name: "tick".into(),
+                ret: "void".into(),
+                args: vec![],
+            }),
+            Export::parse("extern void tick();")
+        );
+        assert_eq!(None, Export::parse("#include \"svdpi.h\""));
+        assert_eq!(None, Export::parse("extern void broken(int);"));
+        assert!(Export::parse_header("extern void tick();").is_empty());
+    }
+
+    #[test]
+    fn dpi_export_errors() {
+        let cases = [
+            quote! { fn f<T>(a: T) {} },
+            quote! { async fn f() {} },
+            quote! { fn f(a: String) {} },
+            quote! { fn f(a: &u32) {} },
+            quote! { fn f(a: &[bool]) {} },
+            quote! { fn f(a: &[u128]) {} },
+            quote! { fn f() -> &'static str { "" } },
+            quote! { fn f() -> u128 { 0 } },
+            quote! { fn f(a: u128) {} },
+            quote! { fn f(a: &mut u128) {} },
+            quote! { fn f(#[bits(8)] a: u32) {} },
+            quote! { fn f(#[bits(0)] a: u128) {} },
+            quote! { fn f(#[bits(129)] a: u128) {} },
+            quote! { fn f(a: &[Logic<8>]) {} },
+            quote! { fn f() -> Logic<8> { Logic::x() } },
+        ];
+        for item in cases {
+            assert!(dpi_export_attribute(quote! {}, item).is_err());
+        }
+        assert!(dpi_export_attribute(quote! { x }, quote! { fn f() {} }).is_err());
+    }
+}
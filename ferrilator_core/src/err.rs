@@ -2,6 +2,7 @@
 pub enum Error {
     Input(String),
     Io(String),
+    Mismatch(String),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -11,6 +12,7 @@ impl std::fmt::Display for Error {
         match self {
             Error::Input(msg) => write!(f, "input: {msg}"),
             Error::Io(msg) => write!(f, "io: {msg}"),
+            Error::Mismatch(msg) => write!(f, "mismatch: {msg}"),
         }
     }
 }
@@ -37,6 +39,17 @@ macro_rules! input {
 }
 pub use input;
 
+#[macro_export]
+macro_rules! mismatch {
+    ($fmt:literal) => {
+        Err(err::Error::Mismatch(format!($fmt)))
+    };
+    ($fmt:literal, $($val:expr),*) => {
+        Err(err::Error::Mismatch(format!($fmt, $($val),*)))
+    };
+}
+pub use mismatch;
+
 impl From<syn::Error> for Error {
     fn from(e: syn::Error) -> Error {
         Error::Input(e.to_string())
@@ -0,0 +1,161 @@
+use crate::err;
+use proc_macro2::Literal;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Expr;
+use syn::ExprLit;
+use syn::Fields;
+use syn::ItemEnum;
+use syn::Lit;
+use syn::LitInt;
+use syn::parse2;
+
+/// Implement `ferrilator::PortType` for an enum whose variants all have an
+/// explicit discriminant, the encoding of the variant in the design. The
+/// width is given by `#[bits(n)]` on the enum, or is the fewest bits that
+/// hold every encoding. Getters return `Err` with the raw value for bit
+/// patterns that are not a variant.
+pub fn derive_hw_enum(item: TokenStream) -> err::Result<TokenStream> {
+    let item: ItemEnum = parse2(item)?;
+    let ident = &item.ident;
+    if !item.generics.params.is_empty() {
+        return err::input!("enum {ident} cannot be generic");
+    }
+
+    let mut variants = vec![];
+    for variant in &item.variants {
+        let name = &variant.ident;
+        if !matches!(variant.fields, Fields::Unit) {
+            return err::input!("variant {name} of {ident} cannot have fields");
+        }
+        let encoding: u64 = match &variant.discriminant {
+            Some((
+                _,
+                Expr::Lit(ExprLit {
+                    lit: Lit::Int(int), ..
+                }),
+            )) => int.base10_parse()?,
+            _ => return err::input!("variant {name} of {ident} needs an explicit encoding"),
+        };
+        if let Some((previous, _)) = variants.iter().find(|(_, other)| *other == encoding) {
+            return err::input!("variants {previous} and {name} of {ident} have the same encoding");
+        }
+        variants.push((name.clone(), encoding));
+    }
+    if variants.is_empty() {
+        return err::input!("enum {ident} needs at least one variant");
+    }
+
+    let max = variants
+        .iter()
+        .map(|(_, encoding)| *encoding)
+        .max()
+        .unwrap();
+    let needed = (u64::BITS - max.leading_zeros()).max(1);
+    let width = match item.attrs.iter().find(|attr| attr.path().is_ident("bits")) {
+        Some(attr) => {
+            let bits: u32 = attr.parse_args::<LitInt>()?.base10_parse()?;
+            if bits == 0 || bits > 64 {
+                return err::input!("enum {ident} must have between 1 and 64 bits");
+            }
+            if bits < needed {
+                return err::input!("encoding {max} of {ident} does not fit in {bits} bits");
+            }
+            bits
+        }
+        None => needed,
+    };
+    let mask = Literal::u128_suffixed(if width == 64 {
+        u64::MAX as u128
+    } else {
+        (1 << width) - 1
+    });
+
+    let to_bits = variants.iter().map(|(name, encoding)| {
+        let encoding = *encoding as u128;
+        quote! { #ident::#name => #encoding, }
+    });
+    let from_bits = variants.iter().map(|(name, encoding)| {
+        quote! { #encoding => Ok(#ident::#name), }
+    });
+
+    Ok(quote! {
+        impl ::ferrilator::PortType for #ident {
+            const WIDTH: u32 = #width;
+            type Read = ::std::result::Result<#ident, u64>;
+
+            fn to_bits(&self) -> u128 {
+                match self {
+                    #(#to_bits)*
+                }
+            }
+
+            fn from_bits(bits: u128) -> Self::Read {
+                match (bits & #mask) as u64 {
+                    #(#from_bits)*
+                    other => Err(other),
+                }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::snapshot;
+
+    #[test]
+    fn hw_enum_derive() -> err::Result<()> {
+        let item = quote! {
+            #[bits(3)]
+            enum State {
+                Idle = 0,
+                Busy = 1,
+                Done = 0b100,
+            }
+        };
+
+        let output = derive_hw_enum(item)?;
+
+        snapshot("hw_enum.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn hw_enum_width() -> err::Result<()> {
+        let widths = [
+            (quote! { enum E { A = 0 } }, "1u32"),
+            (quote! { enum E { A = 0, B = 1 } }, "1u32"),
+            (quote! { enum E { A = 2, B = 0x80 } }, "8u32"),
+            (quote! { #[bits(64)] enum E { A = 0 } }, "64u32"),
+        ];
+        for (item, width) in widths {
+            let output = derive_hw_enum(item)?.to_string();
+            assert!(
+                output.contains(&format!("const WIDTH : u32 = {width}")),
+                "{output}"
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn hw_enum_errors() {
+        let cases = [
+            quote! { enum E { A, B } },
+            quote! { enum E { A = 1, B = 1 } },
+            quote! { enum E { A = 1, B(u8) = 2 } },
+            quote! { enum E { A = X } },
+            quote! { enum E {} },
+            quote! { #[bits(2)] enum E { A = 4 } },
+            quote! { #[bits(65)] enum E { A = 4 } },
+            quote! { #[bits(0)] enum E { A = 0 } },
+            quote! { enum E<T> { A = 0 } },
+            quote! { struct E { a: u8 } },
+        ];
+        for item in cases {
+            assert!(derive_hw_enum(item).is_err());
+        }
+    }
+}
@@ -1,4 +1,7 @@
+pub mod dpi;
 pub mod err;
+pub mod hw_enum;
+pub mod packed;
 
 use proc_macro2::Ident;
 use proc_macro2::Span;
@@ -7,151 +10,849 @@ use proc_macro2::TokenTree;
 use quote::ToTokens;
 use quote::TokenStreamExt;
 use quote::quote;
+use syn::Attribute;
 use syn::ItemStruct;
 use syn::Meta;
+use syn::Token;
 use syn::Visibility;
+use syn::parse::Parser;
 use syn::parse2;
+use syn::punctuated::Punctuated;
+
+/// Clock period used when a single clock is declared without one.
+const CLOCK_PERIOD_PS: u64 = 10_000;
 
 pub fn ferrilate_attribute(attr: TokenStream, item: TokenStream) -> err::Result<TokenStream> {
     let module = Module::from_attribute(attr, item)?;
+    module_code(&module)
+}
 
+/// The struct, impls and externs generated for `module`.
+fn module_code(module: &Module) -> err::Result<TokenStream> {
     let vis: TokenStream = module.vis.parse()?;
     let ident = &module.ident;
     let link_name = format!("V{}", module.name);
     let mod_new = Ident::new(&format!("{}_new", module.name), Span::call_site());
     let mod_del = Ident::new(&format!("{}_del", module.name), Span::call_site());
     let mod_eval = Ident::new(&format!("{}_eval", module.name), Span::call_site());
+    let mod_final = Ident::new(&format!("{}_final", module.name), Span::call_site());
+
+    let clocks = ClockCode::new(module);
+    let clock_field = &clocks.field;
+    let clocked_fns = &clocks.fns;
+    let clocked_dut_fns = &clocks.dut_fns;
+    let clocked_drop = &clocks.drop;
+    let clocked_impl = &clocks.impls;
+
+    let reset_fns = match &module.reset {
+        Some(reset) => reset.reset_fn(module)?,
+        None => quote! {},
+    };
+
+    let save = SaveCode::new(module);
+    let save_fns = &save.fns;
+    let save_impl = &save.impls;
+    let save_externs = &save.externs;
 
-    let clocked_fns = match &module.clock {
-        Some((name, data_type)) => {
-            let set_fn = Ident::new(&format!("set_{}", name), Span::call_site());
-            let (tru, fls) = data_type.true_false();
-            let tru = Ident::new(tru, Span::call_site());
-            let fls = Ident::new(fls, Span::call_site());
+    let (coverage_fns, coverage_externs) = if module.options.coverage {
+        let mod_write_coverage = Ident::new(
+            &format!("{}_write_coverage", module.name),
+            Span::call_site(),
+        );
+        (
             quote! {
-                fn tick(&mut self) {
-                    self.#set_fn(#tru);
-                    self.eval();
-                    self.#set_fn(#fls);
-                    self.eval();
+                fn write_coverage(&self, path: impl AsRef<::std::path::Path>) -> ::ferrilator::err::Result<()> {
+                    ::ferrilator::coverage::write(path.as_ref(), |path| unsafe {
+                        #mod_write_coverage(self.dut, path)
+                    })
                 }
-            }
-        }
-        None => {
-            quote! {}
-        }
+            },
+            quote! {
+                fn #mod_write_coverage(dut: *mut (), path: *const ::std::ffi::c_char);
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
+    };
+
+    let (dpi_include, dpi_externs) = if module.options.dpi {
+        let file = format!("/{}_dpi.rs", module.name);
+        let mod_dpi_scope = Ident::new(&format!("{}_dpi_scope", module.name), Span::call_site());
+        (
+            quote! {
+                // written by ferrilator::build from the exports of the design
+                include!(concat!(env!("OUT_DIR"), #file));
+            },
+            quote! {
+                fn #mod_dpi_scope(dut: *mut ());
+            },
+        )
+    } else {
+        (quote! {}, quote! {})
     };
 
     let mut rs_fns = vec![];
 
     let mut cc_fns = vec![];
 
+    let mut port_infos = vec![];
+    let mut set_arms = vec![];
+    let mut get_arms = vec![];
+    let mut watch_fields = vec![];
+    let mut watch_types = vec![];
+    let mut watch_inits = vec![];
+    let mut watch_checks = vec![];
+    let mut width_checks = vec![];
+
     for port in &module.ports {
         let data_type = port.data_type;
+        let port_name = &port.name;
+        // elements of an unpacked array are accessed through `_at` methods
+        // taking the index, which the C functions take first
+        let (suffix, index_param, index_arg) = match port.array_len {
+            Some(_) => ("_at", quote! { , index: usize }, quote! { , index }),
+            None => ("", quote! {}, quote! {}),
+        };
+        let check_index = match port.array_len {
+            Some(len) => {
+                let message =
+                    format!("index {{index}} out of range for {port_name} of length {len}");
+                quote! {
+                    assert!(index < #len, #message);
+                }
+            }
+            None => quote! {},
+        };
+        // ports of a `PortType` type are passed as u128 and converted here,
+        // getters return its `Read` type
+        let rust_type = match &port.rust_type {
+            Some(ty) => Some(ty.parse::<TokenStream>()?),
+            None => None,
+        };
+        let (value_type, read_type) = match &rust_type {
+            Some(ty) => (
+                quote! { #ty },
+                quote! { <#ty as ::ferrilator::PortType>::Read },
+            ),
+            None => (quote! { #data_type }, quote! { #data_type }),
+        };
+        // `ferrilator::build` sets the width of each port of the model
+        if let Some(ty) = &rust_type {
+            let env = format!("FERRILATOR_WIDTH_{}_{port_name}", module.name);
+            let message =
+                format!("the PortType::WIDTH of {port_name} is not the width of the port");
+            width_checks.push(quote! {
+                const _: () = assert!(
+                    ::ferrilator::PortInfo::width_matches(
+                        <#ty as ::ferrilator::PortType>::WIDTH,
+                        option_env!(#env),
+                    ),
+                    #message
+                );
+            });
+        }
 
         if port.input {
             let ext_name = Ident::new(
                 &format!("{}_set_{}", module.name, port.name),
                 Span::call_site(),
             );
-            let fn_name = Ident::new(&format!("set_{}", port.name), Span::call_site());
+            let fn_name = Ident::new(&format!("set_{}{suffix}", port.name), Span::call_site());
+            let write = quote! {
+                let arr = [
+                    (value & 0xffff_ffff) as u32,
+                    ((value >> 32) & 0xffff_ffff) as u32,
+                    ((value >> 64) & 0xffff_ffff) as u32,
+                    ((value >> 96) & 0xffff_ffff) as u32,
+                ];
+                unsafe { #ext_name(self.dut #index_arg, &arr) };
+            };
             if data_type == DataType::U128 {
                 cc_fns.push(quote! {
-                    fn #ext_name(dut: *mut(), blocks: &[u32; 4]);
+                    fn #ext_name(dut: *mut() #index_param, blocks: &[u32; 4]);
                 });
+                // only the low `WIDTH` bits of `to_bits` are the value
+                let to_bits = match &rust_type {
+                    Some(ty) => quote! {
+                        let value = ::ferrilator::Value::new(
+                            ::ferrilator::PortType::to_bits(&value),
+                            <#ty as ::ferrilator::PortType>::WIDTH,
+                        )
+                        .bits();
+                    },
+                    None => quote! {},
+                };
                 rs_fns.push(quote! {
-                    fn #fn_name(&mut self, value: #data_type) {
-                        let arr = [
-                            (value & 0xffff_ffff) as u32,
-                            ((value >> 32) & 0xffff_ffff) as u32,
-                            ((value >> 64) & 0xffff_ffff) as u32,
-                            ((value >> 96) & 0xffff_ffff) as u32,
-                        ];
-                        unsafe { #ext_name(self.dut, &arr) };
+                    fn #fn_name(&mut self #index_param, value: #value_type) {
+                        #check_index
+                        #to_bits
+                        #write
                     }
                 });
             } else {
                 cc_fns.push(quote! {
-                    fn #ext_name(dut: *mut(), value: #data_type);
+                    fn #ext_name(dut: *mut() #index_param, value: #data_type);
                 });
                 rs_fns.push(quote! {
-                    fn #fn_name(&mut self, value: #data_type) {
-                        unsafe { #ext_name(self.dut, value) };
+                    fn #fn_name(&mut self #index_param, value: #data_type) {
+                        #check_index
+                        unsafe { #ext_name(self.dut #index_arg, value) };
                     }
                 });
             }
+
+            // values given by name are written as they are, whether or not
+            // they have a Rust value
+            let set_value = |index: Option<usize>| {
+                let from_bits = data_type.cast_bits(quote! { value.bits() });
+                match (&rust_type, index) {
+                    (Some(_), Some(index)) => quote! {
+                        {
+                            let index = #index;
+                            let value = value.bits();
+                            #write
+                        }
+                    },
+                    (Some(_), None) => quote! {
+                        {
+                            let value = value.bits();
+                            #write
+                        }
+                    },
+                    (None, Some(index)) => quote! { self.#fn_name(#index, #from_bits) },
+                    (None, None) => quote! { self.#fn_name(#from_bits) },
+                }
+            };
+            match port.array_len {
+                Some(len) => {
+                    let set_all = Ident::new(&format!("set_{}", port.name), Span::call_site());
+                    rs_fns.push(quote! {
+                        fn #set_all(&mut self, values: [#value_type; #len]) {
+                            for (index, value) in values.into_iter().enumerate() {
+                                self.#fn_name(index, value);
+                            }
+                        }
+                    });
+                    for index in 0..len {
+                        let element = format!("{port_name}[{index}]");
+                        let set_value = set_value(Some(index));
+                        set_arms.push(quote! {
+                            #element => #set_value,
+                        });
+                    }
+                }
+                None => {
+                    let set_value = set_value(None);
+                    set_arms.push(quote! {
+                        #port_name => #set_value,
+                    });
+                }
+            }
         }
 
+        // every port can be read, but only outputs get a public getter
+        let ext_name = Ident::new(
+            &format!("{}_get_{}", module.name, port.name),
+            Span::call_site(),
+        );
+        let read = if data_type == DataType::U128 {
+            cc_fns.push(quote! {
+                fn #ext_name(dut: *mut() #index_param, blocks: &mut [u32]);
+            });
+            quote! {
+                {
+                    let mut arr = [0u32; 4];
+                    unsafe { #ext_name(self.dut #index_arg, &mut arr) };
+                    (arr[0] as u128)
+                    | ((arr[1] as u128) << 32)
+                    | ((arr[2] as u128) << 64)
+                    | ((arr[3] as u128) << 96)
+                }
+            }
+        } else {
+            cc_fns.push(quote! {
+                fn #ext_name(dut: *mut() #index_param) -> #data_type;
+            });
+            quote! {
+                unsafe { #ext_name(self.dut #index_arg) }
+            }
+        };
+
         if port.output {
-            let ext_name = Ident::new(
-                &format!("{}_get_{}", module.name, port.name),
-                Span::call_site(),
-            );
             let fn_name = Ident::new(&format!("get_{}", port.name), Span::call_site());
-            if data_type == DataType::U128 {
-                cc_fns.push(quote! {
-                    fn #ext_name(dut: *mut(), blocks: &mut [u32]);
-                });
-                rs_fns.push(quote! {
-                    fn #fn_name(&self) -> #data_type {
-                        let mut arr = [0u32; 4];
-                        unsafe { #ext_name(self.dut, &mut arr) };
-                        (arr[0] as u128)
-                        | ((arr[1] as u128) << 32)
-                        | ((arr[2] as u128) << 64)
-                        | ((arr[3] as u128) << 96)
-                    }
-                });
-            } else {
-                cc_fns.push(quote! {
-                    fn #ext_name(dut: *mut()) -> #data_type;
+            let from_bits = match &rust_type {
+                Some(ty) => quote! { <#ty as ::ferrilator::PortType>::from_bits(#read) },
+                None => read.clone(),
+            };
+            let watch_type = match port.array_len {
+                Some(len) => {
+                    let get_at = Ident::new(&format!("get_{}_at", port.name), Span::call_site());
+                    rs_fns.push(quote! {
+                        fn #get_at(&self, index: usize) -> #read_type {
+                            #check_index
+                            #from_bits
+                        }
+
+                        fn #fn_name(&self) -> [#read_type; #len] {
+                            ::std::array::from_fn(|index| self.#get_at(index))
+                        }
+                    });
+                    quote! { [#read_type; #len] }
+                }
+                None => {
+                    rs_fns.push(quote! {
+                        fn #fn_name(&self) -> #read_type {
+                            #from_bits
+                        }
+                    });
+                    read_type.clone()
+                }
+            };
+
+            let watch = Ident::new(&format!("watch_{}", port.name), Span::call_site());
+            let on_change = Ident::new(&format!("on_change_{}", port.name), Span::call_site());
+            watch_fields.push(quote! {
+                #watch: ::ferrilator::OnChange<#watch_type>,
+            });
+            watch_types.push(quote! { ::ferrilator::OnChange<#watch_type> });
+            watch_inits.push(quote! {
+                #watch: ::ferrilator::OnChange::new(),
+            });
+            // only read ports that have callbacks
+            watch_checks.push(quote! {
+                if self.#watch.active() {
+                    let now = self.#fn_name();
+                    self.#watch.update(now);
+                }
+            });
+            rs_fns.push(quote! {
+                fn #on_change(&mut self, callback: impl FnMut(#watch_type, #watch_type) + Send + 'static) {
+                    let now = self.#fn_name();
+                    self.#watch.register(now, callback);
+                }
+            });
+        }
+
+        let (width, to_value) = match &rust_type {
+            Some(ty) => {
+                let width = quote! { <#ty as ::ferrilator::PortType>::WIDTH };
+                (
+                    width.clone(),
+                    quote! { ::ferrilator::Value::new(#read, #width) },
+                )
+            }
+            None => {
+                let width = data_type.width();
+                (
+                    quote! { #width },
+                    quote! { ::ferrilator::Value::from(#read) },
+                )
+            }
+        };
+        let signed = data_type.signed();
+        let input = port.input;
+        let output = port.output;
+        // each element of an array is a port of its own, named like `data[0]`
+        match port.array_len {
+            Some(len) => {
+                for index in 0..len {
+                    let element = format!("{port_name}[{index}]");
+                    get_arms.push(quote! {
+                        #element => {
+                            let index = #index;
+                            #to_value
+                        }
+                    });
+                    port_infos.push(quote! {
+                        ::ferrilator::PortInfo::new(#element, #width, #signed, #input, #output),
+                    });
+                }
+            }
+            None => {
+                get_arms.push(quote! {
+                    #port_name => #to_value,
                 });
-                rs_fns.push(quote! {
-                    fn #fn_name(&self) -> #data_type {
-                        unsafe { #ext_name(self.dut) }
-                    }
+                port_infos.push(quote! {
+                    ::ferrilator::PortInfo::new(#port_name, #width, #signed, #input, #output),
                 });
             }
         }
     }
 
+    // check_write fails for every port of a design without inputs
+    let set_body = if set_arms.is_empty() {
+        quote! {
+            ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)
+        }
+    } else {
+        quote! {
+            ::ferrilator::PortInfo::find(self.ports(), name)?.check_write(value)?;
+            match name {
+                #(#set_arms)*
+                _ => unreachable!(),
+            }
+            Ok(())
+        }
+    };
+
+    // every field other than the model must be Send on its own
+    let send_types: Vec<_> = clocks.types.iter().chain(&watch_types).collect();
+    let send_check = if send_types.is_empty() {
+        quote! {}
+    } else {
+        quote! {
+            #[cfg(not(ferrilator_global_context))]
+            const _: () = {
+                const fn assert_send<T: Send>() {}
+                #(assert_send::<#send_types>();)*
+            };
+        }
+    };
+
+    let clock_init = &clocks.init;
+    let init = quote! {
+        Self {
+            dut,
+            time: 0,
+            #clock_init
+            #(#watch_inits)*
+        }
+    };
+    let mut setup = clocks.idle.clone();
+    // the first evaluation takes the levels of the clocks as the starting
+    // point for edge detection, so it must come before the first edge
+    setup.push(quote! { dut.eval(); });
+    if module.reset.as_ref().is_some_and(|reset| reset.auto) {
+        setup.push(quote! { dut.reset(); });
+    }
+
     Ok(quote! {
         #vis struct #ident {
             dut: *mut (),
+            time: u64,
+            #clock_field
+            #(#watch_fields)*
         }
 
         impl #ident {
             fn new() -> Self {
-                let dut = unsafe { #mod_new() };
-                Self { dut }
+                Self::with_config(&::ferrilator::Config::new())
+            }
+
+            fn with_threads(threads: u32) -> Self {
+                Self::with_config(&::ferrilator::Config::new().with_threads(threads))
+            }
+
+            fn with_seed(seed: u64) -> Self {
+                Self::with_config(&::ferrilator::Config::new().with_seed(seed))
+            }
+
+            fn with_args(args: &[&str]) -> Self {
+                Self::with_config(&::ferrilator::Config::new().with_args(args))
+            }
+
+            fn with_config(config: &::ferrilator::Config) -> Self {
+                let args = config.c_args();
+                let dut = unsafe { #mod_new(config.threads(), args.argc(), args.argv()) };
+                if let (Some(seed), Some(verilator_seed)) = (config.seed(), config.verilator_seed()) {
+                    eprintln!("ferrilator: random seed {seed}, verilator seed {verilator_seed}");
+                }
+                let mut dut = #init;
+                #(#setup)*
+                dut
             }
 
             fn eval(&mut self) {
                 unsafe { #mod_eval(self.dut) };
+                #(#watch_checks)*
             }
 
             #clocked_fns
 
+            #reset_fns
+
+            #save_fns
+
+            #coverage_fns
+
             #(#rs_fns)*
         }
 
+        impl ::ferrilator::Dut for #ident {
+            fn eval(&mut self) {
+                Self::eval(self);
+            }
+
+            fn time(&self) -> u64 {
+                self.time
+            }
+
+            #clocked_dut_fns
+
+            fn finish(&mut self) {
+                unsafe { #mod_final(self.dut) };
+            }
+
+            fn ports(&self) -> &[::ferrilator::PortInfo] {
+                const PORTS: &[::ferrilator::PortInfo] = &[
+                    #(#port_infos)*
+                ];
+                PORTS
+            }
+
+            fn set_port(
+                &mut self,
+                name: &str,
+                value: ::ferrilator::Value,
+            ) -> ::ferrilator::err::Result<()> {
+                #set_body
+            }
+
+            fn get_port(&self, name: &str) -> ::ferrilator::err::Result<::ferrilator::Value> {
+                ::ferrilator::PortInfo::find(self.ports(), name)?;
+                Ok(match name {
+                    #(#get_arms)*
+                    _ => unreachable!(),
+                })
+            }
+        }
+
+        #clocked_impl
+
+        #save_impl
+
+        #dpi_include
+
+        // the model is owned along with a VerilatedContext of its own, so it
+        // shares no state with other models and can move between threads,
+        // `ferrilator::build` sets the cfg for releases without contexts
+        #[cfg(not(ferrilator_global_context))]
+        unsafe impl Send for #ident {}
+
+        #send_check
+
+        #(#width_checks)*
+
         impl Drop for #ident {
             fn drop(&mut self) {
+                #clocked_drop
                 unsafe { #mod_del(self.dut) };
             }
         }
 
         #[link(name = #link_name)]
         unsafe extern "C" {
-            fn #mod_new() -> *mut ();
+            fn #mod_new(
+                threads: u32,
+                argc: i32,
+                argv: *const *const ::std::ffi::c_char,
+            ) -> *mut ();
             fn #mod_del(dut: *mut ());
             fn #mod_eval(dut: *mut ());
+            fn #mod_final(dut: *mut ());
+
+            #save_externs
+
+            #coverage_externs
+
+            #dpi_externs
 
             #(#cc_fns)*
         }
     })
 }
 
+/// The parts of a binding built with the `savable` option.
+struct SaveCode {
+    fns: TokenStream,
+    impls: TokenStream,
+    externs: TokenStream,
+}
+
+impl SaveCode {
+    fn new(module: &Module) -> SaveCode {
+        if !module.options.savable {
+            return SaveCode {
+                fns: quote! {},
+                impls: quote! {},
+                externs: quote! {},
+            };
+        }
+
+        let ident = &module.ident;
+        let mod_save = Ident::new(&format!("{}_save", module.name), Span::call_site());
+        let mod_restore = Ident::new(&format!("{}_restore", module.name), Span::call_site());
+        // the time and the state of each clock are saved alongside the model
+        let len = 1 + 3 * module.clocks.len();
+        let (save_clocks, restore_clocks) = if module.clocks.is_empty() {
+            (quote! {}, quote! {})
+        } else {
+            (
+                quote! { words.extend(self.clocks.save()); },
+                quote! { self.clocks.restore(&words[1..]); },
+            )
+        };
+
+        let fns = quote! {
+            fn save(&self, path: impl AsRef<::std::path::Path>) -> ::ferrilator::err::Result<()> {
+                let mut words = vec![self.time];
+                #save_clocks
+                ::ferrilator::save::write(path.as_ref(), &words, |path, words, len| unsafe {
+                    #mod_save(self.dut, path, words, len)
+                })
+            }
+
+            fn restore(&mut self, path: impl AsRef<::std::path::Path>) -> ::ferrilator::err::Result<()> {
+                let mut words = vec![0; #len];
+                let dut = self.dut;
+                ::ferrilator::save::read(path.as_ref(), &mut words, |path, words, len| unsafe {
+                    #mod_restore(dut, path, words, len)
+                })?;
+                self.time = words[0];
+                #restore_clocks
+                Ok(())
+            }
+
+            fn snapshot(&self) -> ::ferrilator::err::Result<::ferrilator::save::Snapshot> {
+                ::ferrilator::save::Snapshot::capture(|path| self.save(path))
+            }
+
+            fn restore_snapshot(
+                &mut self,
+                snapshot: &::ferrilator::save::Snapshot,
+            ) -> ::ferrilator::err::Result<()> {
+                snapshot.apply(|path| self.restore(path))
+            }
+        };
+        let impls = quote! {
+            impl ::ferrilator::Savable for #ident {
+                fn save(&self, path: &::std::path::Path) -> ::ferrilator::err::Result<()> {
+                    Self::save(self, path)
+                }
+
+                fn restore(&mut self, path: &::std::path::Path) -> ::ferrilator::err::Result<()> {
+                    Self::restore(self, path)
+                }
+            }
+        };
+        let externs = quote! {
+            fn #mod_save(
+                dut: *mut (),
+                path: *const ::std::ffi::c_char,
+                words: *const u64,
+                len: usize,
+            ) -> bool;
+            fn #mod_restore(
+                dut: *mut (),
+                path: *const ::std::ffi::c_char,
+                words: *mut u64,
+                len: usize,
+            ) -> bool;
+        };
+        SaveCode {
+            fns,
+            impls,
+            externs,
+        }
+    }
+}
+
+/// The parts of a binding that depend on its clocks.
+struct ClockCode {
+    field: TokenStream,
+    /// The types of `field`.
+    types: Vec<TokenStream>,
+    init: TokenStream,
+    idle: Vec<TokenStream>,
+    fns: TokenStream,
+    /// Methods of the `Dut` impl.
+    dut_fns: TokenStream,
+    impls: TokenStream,
+    /// Run when the binding is dropped, before the model is deleted.
+    drop: TokenStream,
+}
+
+impl ClockCode {
+    fn new(module: &Module) -> ClockCode {
+        let ident = &module.ident;
+        if module.clocks.is_empty() {
+            return ClockCode {
+                field: quote! {},
+                types: vec![],
+                init: quote! {},
+                idle: vec![],
+                fns: quote! {},
+                dut_fns: quote! {},
+                impls: quote! {},
+                drop: quote! {},
+            };
+        }
+
+        let mut schedule = vec![];
+        let mut names = vec![];
+        let mut idle = vec![];
+        let mut step_arms = vec![];
+        for (index, clock) in module.clocks.iter().enumerate() {
+            let set_fn = Ident::new(&format!("set_{}", clock.name), Span::call_site());
+            let period_ps = clock.period_ps();
+            // a negedge clock idles high so its first falling edge is a real one
+            let high = clock.edge == Edge::Negedge;
+            schedule.push(quote! { (#period_ps, #high), });
+            names.push(clock.name.as_str());
+            if high {
+                let (tru, _) = clock.data_type.true_false();
+                idle.push(quote! { dut.#set_fn(#tru); });
+            }
+            let level = clock.data_type.cast_bool(quote! { level });
+            step_arms.push(quote! { #index => self.#set_fn(#level), });
+        }
+        let mut field = quote! {
+            clocks: ::ferrilator::Scheduler,
+        };
+        let mut types = vec![quote! { ::ferrilator::Scheduler }];
+        let mut init = quote! {
+            clocks: ::ferrilator::Scheduler::new(&[#(#schedule)*]),
+        };
+        // cycles are counted on the first clock
+        let dut_fns = quote! {
+            fn cycle(&self) -> u64 {
+                self.clocks.cycles(0)
+            }
+        };
+        let mut fns = quote! {
+            fn step(&mut self) {
+                let (time, edges) = self.clocks.advance();
+                self.time = time;
+                for (index, level) in edges {
+                    match index {
+                        #(#step_arms)*
+                        _ => unreachable!(),
+                    }
+                }
+                self.eval();
+            }
+
+            fn run_for(&mut self, ns: u64) {
+                let end = self.time + ns * 1000;
+                while self.clocks.next_edge().is_some_and(|next| next <= end) {
+                    self.step();
+                }
+                self.time = end;
+            }
+        };
+        let mut impls = quote! {
+            impl ::ferrilator::Scheduled for #ident {
+                fn clocks(&self) -> &[&'static str] {
+                    &[#(#names),*]
+                }
+
+                fn step(&mut self) {
+                    Self::step(self);
+                }
+
+                fn run_for(&mut self, ns: u64) {
+                    Self::run_for(self, ns);
+                }
+            }
+        };
+
+        // a single clock can also be driven an edge at a time
+        let mut drop = quote! {};
+        if let [clock] = &module.clocks[..] {
+            let set_fn = Ident::new(&format!("set_{}", clock.name), Span::call_site());
+            let (tru, fls) = clock.data_type.true_false();
+            let high_ps = clock.period_ps() / 2;
+            let low_ps = clock.period_ps() - high_ps;
+            let tick = match clock.edge {
+                Edge::Posedge => quote! {
+                    self.posedge();
+                    self.negedge();
+                },
+                Edge::Negedge => quote! {
+                    self.negedge();
+                    self.posedge();
+                },
+            };
+            fns.extend(quote! {
+                fn posedge(&mut self) {
+                    self.#set_fn(#tru);
+                    self.time += #low_ps;
+                    self.clocks.drive(0, true, self.time);
+                    self.eval();
+                }
+
+                fn negedge(&mut self) {
+                    self.#set_fn(#fls);
+                    self.time += #high_ps;
+                    self.clocks.drive(0, false, self.time);
+                    self.eval();
+                }
+
+                fn tick(&mut self) {
+                    #tick
+                    if let Some(mut history) = self.history.take() {
+                        history.record(self);
+                        self.history = Some(history);
+                    }
+                }
+
+                fn tick_n(&mut self, n: usize) {
+                    for _ in 0..n {
+                        self.tick();
+                    }
+                }
+
+                fn record_history(&mut self, cycles: usize) {
+                    self.history = Some(::ferrilator::History::new(cycles));
+                }
+
+                fn history(&self) -> Option<&::ferrilator::History> {
+                    self.history.as_ref()
+                }
+            });
+            field.extend(quote! {
+                history: Option<::ferrilator::History>,
+            });
+            types.push(quote! { Option<::ferrilator::History> });
+            init.extend(quote! {
+                history: None,
+            });
+            // printed when a failed test unwinds past the design
+            drop = quote! {
+                if let Some(history) = &self.history {
+                    history.print_if_panicking();
+                }
+            };
+            impls.extend(quote! {
+                impl ::ferrilator::Clocked for #ident {
+                    fn posedge(&mut self) {
+                        Self::posedge(self);
+                    }
+
+                    fn negedge(&mut self) {
+                        Self::negedge(self);
+                    }
+
+                    fn tick(&mut self) {
+                        Self::tick(self);
+                    }
+                }
+            });
+        }
+
+        ClockCode {
+            field,
+            types,
+            init,
+            idle,
+            fns,
+            dut_fns,
+            impls,
+            drop,
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum DataType {
     Bool,
@@ -183,18 +884,49 @@ impl DataType {
         })
     }
 
-    fn true_false(&self) -> (&str, &str) {
+    /// Width in bits of a port of this type.
+    pub fn width(&self) -> u32 {
+        match self {
+            DataType::Bool => 1,
+            DataType::U8 => 8,
+            DataType::U16 => 16,
+            DataType::U32 => 32,
+            DataType::U64 => 64,
+            DataType::I8 => 8,
+            DataType::I16 => 16,
+            DataType::I32 => 32,
+            DataType::I64 => 64,
+            DataType::U128 => 128,
+        }
+    }
+
+    pub fn signed(&self) -> bool {
+        matches!(
+            self,
+            DataType::I8 | DataType::I16 | DataType::I32 | DataType::I64
+        )
+    }
+
+    /// Convert an expression of type `u128` to this type, truncating.
+    fn cast_bits(&self, bits: TokenStream) -> TokenStream {
+        match self {
+            DataType::Bool => quote! { #bits != 0 },
+            other => quote! { #bits as #other },
+        }
+    }
+
+    /// Convert an expression of type `bool` to this type.
+    fn cast_bool(&self, level: TokenStream) -> TokenStream {
         match self {
-            DataType::Bool => ("true", "false"),
-            DataType::U8 => ("1", "0"),
-            DataType::U16 => ("1", "0"),
-            DataType::U32 => ("1", "0"),
-            DataType::U64 => ("1", "0"),
-            DataType::I8 => ("1", "0"),
-            DataType::I16 => ("1", "0"),
-            DataType::I32 => ("1", "0"),
-            DataType::I64 => ("1", "0"),
-            DataType::U128 => ("1", "0"),
+            DataType::Bool => level,
+            other => quote! { #level as #other },
+        }
+    }
+
+    fn true_false(&self) -> (TokenStream, TokenStream) {
+        match self {
+            DataType::Bool => (quote! { true }, quote! { false }),
+            _ => (quote! { 1 }, quote! { 0 }),
         }
     }
 }
@@ -222,24 +954,216 @@ impl ToTokens for DataType {
     }
 }
 
+/// The edge of a clock that the design is sensitive to.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Edge {
+    Posedge,
+    Negedge,
+}
+
 #[derive(Debug, PartialEq)]
-pub struct Port {
+pub struct Clock {
     name: String,
     data_type: DataType,
-    input: bool,
-    output: bool,
+    edge: Edge,
+    period_ps: Option<u64>,
 }
 
-impl Port {
+impl Clock {
+    /// Parse `#[clock]` or `#[clock(...)]` with any of `posedge`, `negedge`
+    /// and `period_ps = N`.
+    fn from_attribute(name: String, data_type: DataType, attr: &Attribute) -> err::Result<Clock> {
+        let mut edge = Edge::Posedge;
+        let mut period_ps = None;
+        if let Meta::List(list) = &attr.meta {
+            for arg in list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+                let key = arg.path().get_ident().map(|id| id.to_string());
+                match (key.as_deref(), &arg) {
+                    (Some("posedge"), Meta::Path(_)) => edge = Edge::Posedge,
+                    (Some("negedge"), Meta::Path(_)) => edge = Edge::Negedge,
+                    (Some("period_ps"), Meta::NameValue(nv)) => {
+                        let period = int_arg(&name, nv)?;
+                        if period < 2 {
+                            return err::input!("period_ps of clock {name} must be at least 2");
+                        }
+                        period_ps = Some(period);
+                    }
+                    _ => {
+                        return err::input!(
+                            "unexpected argument {} to clock {name}",
+                            as_tokens(&arg)
+                        );
+                    }
+                }
+            }
+        }
+
+        Ok(Clock {
+            name,
+            data_type,
+            edge,
+            period_ps,
+        })
+    }
+
     pub fn name(&self) -> &String {
         &self.name
     }
 
-    pub fn data_type(&self) -> DataType {
-        self.data_type
+    pub fn edge(&self) -> Edge {
+        self.edge
     }
 
-    pub fn input(&self) -> bool {
+    /// The declared period, or the default for a lone clock.
+    pub fn period_ps(&self) -> u64 {
+        self.period_ps.unwrap_or(CLOCK_PERIOD_PS)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Reset {
+    name: String,
+    data_type: DataType,
+    active_low: bool,
+    cycles: u64,
+    auto: bool,
+    clock: Option<String>,
+}
+
+impl Reset {
+    /// Parse `#[reset]` or `#[reset(...)]` with any of `active_high`,
+    /// `active_low`, `cycles = N`, `auto` and `clock = name`.
+    fn from_attribute(name: String, data_type: DataType, attr: &Attribute) -> err::Result<Reset> {
+        let mut reset = Reset {
+            name,
+            data_type,
+            active_low: false,
+            cycles: 1,
+            auto: false,
+            clock: None,
+        };
+        let name = &reset.name;
+        if let Meta::List(list) = &attr.meta {
+            for arg in list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+                let key = arg.path().get_ident().map(|id| id.to_string());
+                match (key.as_deref(), &arg) {
+                    (Some("active_high"), Meta::Path(_)) => reset.active_low = false,
+                    (Some("active_low"), Meta::Path(_)) => reset.active_low = true,
+                    (Some("auto"), Meta::Path(_)) => reset.auto = true,
+                    (Some("cycles"), Meta::NameValue(nv)) => reset.cycles = int_arg(name, nv)?,
+                    (Some("clock"), Meta::NameValue(nv)) => {
+                        reset.clock = Some(as_tokens(&nv.value).to_string());
+                    }
+                    _ => {
+                        return err::input!(
+                            "unexpected argument {} to reset {name}",
+                            as_tokens(&arg)
+                        );
+                    }
+                }
+            }
+        }
+        Ok(reset)
+    }
+
+    /// Find the clock that times the reset.
+    fn clock<'a>(&self, clocks: &'a [Clock]) -> err::Result<&'a Clock> {
+        let name = &self.name;
+        match (&self.clock, clocks) {
+            (None, [clock]) => Ok(clock),
+            (None, []) => err::input!("reset {name} needs a clock"),
+            (None, _) => err::input!("reset {name} needs clock = ... to pick one of the clocks"),
+            (Some(wanted), _) => match clocks.iter().find(|clock| &clock.name == wanted) {
+                Some(clock) => Ok(clock),
+                None => err::input!("reset {name} refers to {wanted}, which is not a clock"),
+            },
+        }
+    }
+
+    /// Assert reset, evaluate so the model starts out in reset, run for the
+    /// given number of cycles and release it.
+    fn reset_fn(&self, module: &Module) -> err::Result<TokenStream> {
+        let clock = self.clock(&module.clocks)?;
+        let set_fn = Ident::new(&format!("set_{}", self.name), Span::call_site());
+        let (tru, fls) = self.data_type.true_false();
+        let (on, off) = if self.active_low {
+            (fls, tru)
+        } else {
+            (tru, fls)
+        };
+        let run = if module.clocks.len() == 1 {
+            let cycles = self.cycles as usize;
+            quote! {
+                self.tick_n(#cycles);
+            }
+        } else {
+            let period_ps = clock.period_ps();
+            let cycles = self.cycles;
+            quote! {
+                let end = self.time + #cycles * #period_ps;
+                while self.clocks.next_edge().is_some_and(|next| next <= end) {
+                    self.step();
+                }
+                self.time = end;
+            }
+        };
+        Ok(quote! {
+            fn reset(&mut self) {
+                self.#set_fn(#on);
+                self.eval();
+                #run
+                self.#set_fn(#off);
+                self.eval();
+            }
+        })
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn active_low(&self) -> bool {
+        self.active_low
+    }
+
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Port {
+    name: String,
+    data_type: DataType,
+    array_len: Option<usize>,
+    rust_type: Option<String>,
+    input: bool,
+    output: bool,
+}
+
+impl Port {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    /// The type of the port, or of its elements if it is an array.
+    pub fn data_type(&self) -> DataType {
+        self.data_type
+    }
+
+    /// The number of elements of an unpacked array port like
+    /// `input logic [7:0] data [0:3]`, declared as `data: [u8; 4]`.
+    pub fn array_len(&self) -> Option<usize> {
+        self.array_len
+    }
+
+    /// The Rust type of a port declared with a type implementing
+    /// `ferrilator::PortType`, passed to the model as a `u128`.
+    pub fn rust_type(&self) -> Option<&str> {
+        self.rust_type.as_deref()
+    }
+
+    pub fn input(&self) -> bool {
         self.input
     }
 
@@ -248,21 +1172,128 @@ impl Port {
     }
 }
 
+/// Build options given after the module name, as in
+/// `#[ferrilate(counter, savable)]`.
+#[derive(Debug, Default, PartialEq)]
+pub struct Options {
+    savable: bool,
+    threads: Option<u32>,
+    coverage: bool,
+    dpi: bool,
+    dpi_bits: Vec<(String, String, u32)>,
+    x_assign: Option<String>,
+    x_initial: Option<String>,
+}
+
+impl Options {
+    fn parse(tokens: TokenStream) -> err::Result<Options> {
+        let mut options = Options::default();
+        for arg in Punctuated::<Meta, Token![,]>::parse_terminated.parse2(tokens)? {
+            let key = arg.path().get_ident().map(|id| id.to_string());
+            match (key.as_deref(), &arg) {
+                (Some("savable"), Meta::Path(_)) => options.savable = true,
+                (Some("coverage"), Meta::Path(_)) => options.coverage = true,
+                (Some("dpi"), Meta::Path(_)) => options.dpi = true,
+                (Some("dpi"), Meta::List(list)) => {
+                    options.dpi = true;
+                    options.dpi_bits = dpi_bits(list)?;
+                }
+                (Some("x_assign"), Meta::NameValue(nv)) => {
+                    options.x_assign = Some(x_arg(nv, &["0", "1", "fast", "unique"])?);
+                }
+                (Some("x_initial"), Meta::NameValue(nv)) => {
+                    options.x_initial = Some(x_arg(nv, &["0", "fast", "unique"])?);
+                }
+                (Some("threads"), Meta::NameValue(nv)) => {
+                    let threads = int_arg("the module", nv)?;
+                    if !(1..=u32::MAX as u64).contains(&threads) {
+                        return err::input!("threads must be between 1 and {}", u32::MAX);
+                    }
+                    options.threads = Some(threads as u32);
+                }
+                _ => return err::input!("unexpected option {}", as_tokens(&arg)),
+            }
+        }
+        Ok(options)
+    }
+
+    /// Build with `--savable` and generate `save` and `restore`.
+    pub fn savable(&self) -> bool {
+        self.savable
+    }
+
+    /// Build with `--threads` for a multithreaded model.
+    pub fn threads(&self) -> Option<u32> {
+        self.threads
+    }
+
+    /// Build with `--coverage` and generate `write_coverage`.
+    pub fn coverage(&self) -> bool {
+        self.coverage
+    }
+
+    /// Generate methods calling the `export "DPI-C"` functions of the
+    /// design, see [`dpi::dpi_exports`].
+    pub fn dpi(&self) -> bool {
+        self.dpi
+    }
+
+    /// The width of the packed vector argument `arg` of the DPI export
+    /// `export`, given as in `dpi(dump(words = 128))` since the header
+    /// Verilator writes does not have it.
+    pub fn dpi_bits(&self, export: &str, arg: &str) -> Option<u32> {
+        self.dpi_bits
+            .iter()
+            .find(|(name, arg_name, _)| name == export && arg_name == arg)
+            .map(|(_, _, bits)| *bits)
+    }
+
+    /// Build with `--x-assign`, how explicit X values are replaced.
+    pub fn x_assign(&self) -> Option<&str> {
+        self.x_assign.as_deref()
+    }
+
+    /// Build with `--x-initial`, how uninitialised variables start out.
+    pub fn x_initial(&self) -> Option<&str> {
+        self.x_initial.as_deref()
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Module {
     name: String,
     vis: String,
     ident: syn::Ident,
-    clock: Option<(String, DataType)>,
+    clocks: Vec<Clock>,
+    reset: Option<Reset>,
     ports: Vec<Port>,
+    options: Options,
 }
 
 impl Module {
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
     pub fn ports(&self) -> &Vec<Port> {
         &self.ports
     }
 
-    fn from_attribute(attr: TokenStream, item: TokenStream) -> err::Result<Module> {
+    pub fn clocks(&self) -> &[Clock] {
+        &self.clocks
+    }
+
+    pub fn reset(&self) -> Option<&Reset> {
+        self.reset.as_ref()
+    }
+
+    /// Parse the arguments of the `ferrilate` attribute and the struct it is
+    /// applied to.
+    pub fn from_attribute(attr: TokenStream, item: TokenStream) -> err::Result<Module> {
         let mut attr = attr.into_iter();
         let name = match attr.next() {
             Some(TokenTree::Ident(id)) => id.to_string(),
@@ -270,16 +1301,22 @@ impl Module {
             None => return err::input!("expected module name, found nothing"),
         };
 
-        match attr.next() {
-            None => {}
+        let options = match attr.next() {
+            None => Options::default(),
+            Some(TokenTree::Punct(punct)) if punct.as_char() == ',' => {
+                Options::parse(attr.collect())?
+            }
             Some(token) => return err::input!("unexpected attr value: {token}"),
-        }
+        };
 
-        Self::from_struct(name, parse2(item)?)
+        let mut module = Self::from_struct(name, parse2(item)?)?;
+        module.options = options;
+        Ok(module)
     }
 
     pub fn from_struct(name: String, defn: ItemStruct) -> err::Result<Module> {
-        let mut clock = None;
+        let mut clocks: Vec<Clock> = vec![];
+        let mut reset: Option<Reset> = None;
         let mut ports = vec![];
         for field in &defn.fields {
             if field.vis != Visibility::Inherited {
@@ -290,31 +1327,58 @@ impl Module {
                 None => return err::input!("fields must be named"),
             };
 
-            let ty = as_tokens(&field.ty);
-            let data_type = DataType::parse(&ty.to_string())?;
+            let (data_type, rust_type, array_len) = match &field.ty {
+                syn::Type::Array(array) => {
+                    let len = match &array.len {
+                        syn::Expr::Lit(syn::ExprLit {
+                            lit: syn::Lit::Int(int),
+                            ..
+                        }) => int.base10_parse::<usize>()?,
+                        other => {
+                            return err::input!(
+                                "length of array {name} must be an integer, found {}",
+                                as_tokens(other)
+                            );
+                        }
+                    };
+                    if len == 0 {
+                        return err::input!("array {name} cannot be empty");
+                    }
+                    let (data_type, rust_type) = port_type(&array.elem)?;
+                    (data_type, rust_type, Some(len))
+                }
+                ty => {
+                    let (data_type, rust_type) = port_type(ty)?;
+                    (data_type, rust_type, None)
+                }
+            };
             let mut input = false;
             let mut output = false;
             for attr in &field.attrs {
-                match &attr.meta {
-                    Meta::Path(path) => match path.get_ident() {
-                        Some(v) => match v.to_string().as_str() {
-                            "input" => input = true,
-                            "output" => output = true,
-                            "clock" => {
-                                match &clock {
-                                    Some((previous, _)) => {
-                                        return err::input!(
-                                            "fields {previous} and {name} cannot both be declared clock"
-                                        );
-                                    }
-                                    None => {}
-                                }
-                                clock = Some((name.clone(), data_type.clone()));
-                            }
-                            _ => {}
-                        },
-                        None => {}
-                    },
+                let Some(ident) = attr.path().get_ident() else {
+                    continue;
+                };
+                let kind = ident.to_string();
+                if (array_len.is_some() || rust_type.is_some())
+                    && (kind == "clock" || kind == "reset")
+                {
+                    return err::input!("{kind} {name} must be bool or an integer");
+                }
+                match kind.as_str() {
+                    "input" => input = true,
+                    "output" => output = true,
+                    "clock" => {
+                        clocks.push(Clock::from_attribute(name.clone(), data_type, attr)?);
+                    }
+                    "reset" => {
+                        if let Some(previous) = &reset {
+                            return err::input!(
+                                "fields {} and {name} cannot both be declared reset",
+                                previous.name
+                            );
+                        }
+                        reset = Some(Reset::from_attribute(name.clone(), data_type, attr)?);
+                    }
                     _ => {}
                 }
             }
@@ -322,11 +1386,34 @@ impl Module {
             ports.push(Port {
                 name,
                 data_type,
+                array_len,
+                rust_type,
                 input,
                 output,
             });
         }
 
+        if clocks.len() > 1 {
+            for clock in &clocks {
+                if clock.period_ps.is_none() {
+                    return err::input!(
+                        "clock {} needs a period_ps when there is more than one clock",
+                        clock.name
+                    );
+                }
+            }
+        }
+
+        if let Some(reset) = &reset {
+            reset.clock(&clocks)?;
+            if !ports
+                .iter()
+                .any(|port| port.name == reset.name && port.input)
+            {
+                return err::input!("reset {} must be an input", reset.name);
+            }
+        }
+
         let vis = as_tokens(&defn.vis).to_string();
         let ident = defn.ident.clone();
 
@@ -334,13 +1421,103 @@ impl Module {
             name,
             vis,
             ident,
-            clock,
+            clocks,
+            reset,
             ports,
+            options: Options::default(),
         })
     }
 }
 
-fn as_tokens<T: ToTokens>(v: &T) -> TokenStream {
+/// Primitive and std types that never implement `ferrilator::PortType`.
+const NOT_PORT_TYPES: &[&str] = &[
+    "usize", "isize", "f32", "f64", "char", "str", "String", "Vec", "Option", "Box",
+];
+
+/// The type of a port, or its elements. Types other than `bool` and the
+/// integers are taken to implement `ferrilator::PortType`, which the compiler
+/// checks, and are passed to the model as `u128`. Integers of other widths
+/// and the types in [`NOT_PORT_TYPES`] are rejected here, rather than by a
+/// missing trait in the generated code.
+fn port_type(ty: &syn::Type) -> err::Result<(DataType, Option<String>)> {
+    let name = as_tokens(ty).to_string();
+    if let Ok(data_type) = DataType::parse(&name) {
+        return Ok((data_type, None));
+    }
+    let syn::Type::Path(path) = ty else {
+        return err::input!("{name} cannot be the type of a port");
+    };
+    let last = path.path.segments.last().map(|seg| seg.ident.to_string());
+    let last = last.unwrap_or_default();
+    let integer = (last.starts_with('u') || last.starts_with('i'))
+        && last.len() > 1
+        && last[1..].chars().all(|c| c.is_ascii_digit());
+    if integer || NOT_PORT_TYPES.contains(&last.as_str()) {
+        return err::input!(
+            "{name} cannot be the type of a port, use bool, an integer from u8 to u128 \
+             or a type implementing ferrilator::PortType"
+        );
+    }
+    Ok((DataType::U128, Some(name)))
+}
+
+/// Read the setting from an argument like `x_assign = unique`.
+fn x_arg(nv: &syn::MetaNameValue, allowed: &[&str]) -> err::Result<String> {
+    let value = as_tokens(&nv.value).to_string();
+    if !allowed.contains(&value.as_str()) {
+        return err::input!(
+            "{} must be one of {}, found {value}",
+            as_tokens(&nv.path),
+            allowed.join(", ")
+        );
+    }
+    Ok(value)
+}
+
+/// Read the widths from an argument like `dpi(dump(words = 128))`, as
+/// export, argument and bits.
+fn dpi_bits(list: &syn::MetaList) -> err::Result<Vec<(String, String, u32)>> {
+    let mut bits = vec![];
+    for export in list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)? {
+        let Meta::List(export) = export else {
+            return err::input!(
+                "dpi takes exports like dump(words = 128), found {}",
+                as_tokens(&export)
+            );
+        };
+        let name = as_tokens(&export.path).to_string();
+        let args = export
+            .parse_args_with(Punctuated::<syn::MetaNameValue, Token![,]>::parse_terminated)?;
+        for arg in args {
+            let arg_name = as_tokens(&arg.path).to_string();
+            let width = int_arg(&format!("export {name}"), &arg)?;
+            if !(1..=128).contains(&width) {
+                return err::input!(
+                    "{arg_name} of export {name} has {width} bits, packed vectors have 1 to 128 bits"
+                );
+            }
+            bits.push((name.clone(), arg_name, width as u32));
+        }
+    }
+    Ok(bits)
+}
+
+/// Read the integer from an argument like `cycles = 4`.
+fn int_arg(owner: &str, nv: &syn::MetaNameValue) -> err::Result<u64> {
+    match &nv.value {
+        syn::Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(int),
+            ..
+        }) => Ok(int.base10_parse()?),
+        other => err::input!(
+            "{} of {owner} must be an integer, found {}",
+            as_tokens(&nv.path),
+            as_tokens(other)
+        ),
+    }
+}
+
+pub(crate) fn as_tokens<T: ToTokens>(v: &T) -> TokenStream {
     let mut ts = TokenStream::new();
     v.to_tokens(&mut ts);
     ts
@@ -373,6 +1550,363 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn ferrilate_negedge() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock(negedge)]
+                #[input]
+                clk: u8,
+
+                #[output]
+                b: i16,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("negedge.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn ferrilate_clocks() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock(period_ps = 1000)]
+                #[input]
+                core_clk: bool,
+
+                #[clock(negedge, period_ps = 2500)]
+                #[input]
+                bus_clk: u8,
+
+                #[output]
+                b: u32,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("clocks.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn ferrilate_reset() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock]
+                #[input]
+                clk: bool,
+
+                #[reset(active_low, cycles = 4, auto)]
+                #[input]
+                rst_n: bool,
+
+                #[output]
+                b: u8,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("reset.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn ferrilate_reset_clocks() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock(period_ps = 1000)]
+                #[input]
+                core_clk: bool,
+
+                #[clock(period_ps = 3000)]
+                #[input]
+                slow_clk: bool,
+
+                #[reset(cycles = 2, clock = slow_clk)]
+                #[input]
+                rst: u8,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("reset_clocks.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn reset_errors() {
+        let cases = [
+            // no clock
+            quote! {
+                struct Example {
+                    #[reset]
+                    #[input]
+                    rst: bool,
+                }
+            },
+            // not an input
+            quote! {
+                struct Example {
+                    #[clock]
+                    #[input]
+                    clk: bool,
+
+                    #[reset]
+                    #[output]
+                    rst: bool,
+                }
+            },
+            // ambiguous clock
+            quote! {
+                struct Example {
+                    #[clock(period_ps = 10)]
+                    #[input]
+                    a: bool,
+
+                    #[clock(period_ps = 20)]
+                    #[input]
+                    b: bool,
+
+                    #[reset]
+                    #[input]
+                    rst: bool,
+                }
+            },
+            // unknown clock
+            quote! {
+                struct Example {
+                    #[clock]
+                    #[input]
+                    clk: bool,
+
+                    #[reset(clock = rst)]
+                    #[input]
+                    rst: bool,
+                }
+            },
+            // bad argument
+            quote! {
+                struct Example {
+                    #[clock]
+                    #[input]
+                    clk: bool,
+
+                    #[reset(cycles = many)]
+                    #[input]
+                    rst: bool,
+                }
+            },
+        ];
+
+        for item in cases {
+            let attr = quote! { ex_module };
+            assert!(Module::from_attribute(attr, item).is_err());
+        }
+    }
+
+    #[test]
+    fn ferrilate_savable() -> err::Result<()> {
+        let attr = quote! { ex_module, savable };
+        let item = quote! {
+            pub struct Example {
+                #[clock]
+                #[input]
+                clk: bool,
+
+                #[output]
+                b: u8,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("savable.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn ferrilate_coverage() -> err::Result<()> {
+        let attr = quote! { ex_module, coverage, threads = 2 };
+        let item = quote! {
+            pub struct Example {
+                #[input]
+                a: u8,
+
+                #[output]
+                b: u8,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("coverage.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn ferrilate_arrays() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[input]
+                data: [u8; 4],
+
+                #[output]
+                wide: [u128; 2],
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("arrays.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn ferrilate_packed() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[input]
+                command: Command,
+
+                #[output]
+                status: Status,
+
+                #[output]
+                history: [Status; 2],
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("packed_ports.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn option_errors() {
+        let item = quote! {
+            struct Example {
+                #[input]
+                a: bool,
+            }
+        };
+        for attr in [
+            quote! { ex_module, savable = 1 },
+            quote! { ex_module, threads = 0 },
+            quote! { ex_module, threads },
+            quote! { ex_module, x_assign = random },
+            quote! { ex_module, x_initial = 1 },
+            quote! { ex_module, sleepy },
+            quote! { ex_module, dpi = true },
+            quote! { ex_module, dpi(dump) },
+            quote! { ex_module, dpi(dump(words)) },
+            quote! { ex_module, dpi(dump(words = 0)) },
+            quote! { ex_module, dpi(dump(words = 129)) },
+            quote! { ex_module, dpi(dump(words = wide)) },
+            quote! { ex_module savable },
+        ] {
+            assert!(Module::from_attribute(attr, item.clone()).is_err());
+        }
+
+        let module = Module::from_attribute(quote! { ex_module, savable, }, item.clone()).unwrap();
+        assert!(module.options().savable());
+        assert_eq!(None, module.options().threads());
+
+        let module =
+            Module::from_attribute(quote! { ex_module, threads = 4 }, item.clone()).unwrap();
+        assert!(!module.options().savable());
+        assert!(!module.options().coverage());
+        assert!(!module.options().dpi());
+        assert_eq!(Some(4), module.options().threads());
+
+        let module = Module::from_attribute(quote! { ex_module, dpi }, item.clone()).unwrap();
+        assert!(module.options().dpi());
+        assert_eq!(None, module.options().dpi_bits("dump", "words"));
+
+        let attr = quote! { ex_module, dpi(dump(words = 128, mask = 3), peek(index = 4)) };
+        let module = Module::from_attribute(attr, item).unwrap();
+        assert!(module.options().dpi());
+        assert_eq!(Some(128), module.options().dpi_bits("dump", "words"));
+        assert_eq!(Some(3), module.options().dpi_bits("dump", "mask"));
+        assert_eq!(Some(4), module.options().dpi_bits("peek", "index"));
+        assert_eq!(None, module.options().dpi_bits("peek", "words"));
+
+        let attr = quote! { ex_module, x_assign = 0, x_initial = unique };
+        let module = Module::from_attribute(attr, quote! { struct Example {} }).unwrap();
+        assert_eq!(Some("0"), module.options().x_assign());
+        assert_eq!(Some("unique"), module.options().x_initial());
+    }
+
+    #[test]
+    fn clock_errors() {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock(sideways)]
+                #[input]
+                clk: bool,
+            }
+        };
+        assert!(Module::from_attribute(attr, item).is_err());
+
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock(period_ps = 1000)]
+                #[input]
+                a: bool,
+
+                #[clock]
+                #[input]
+                b: bool,
+            }
+        };
+        assert!(Module::from_attribute(attr, item).is_err());
+
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[clock(period_ps = "fast")]
+                #[input]
+                clk: bool,
+            }
+        };
+        assert!(Module::from_attribute(attr, item).is_err());
+    }
+
+    #[test]
+    fn array_errors() {
+        for item in [
+            quote! { struct Example { #[input] data: [u8; N] } },
+            quote! { struct Example { #[input] data: [u8; 0] } },
+            quote! { struct Example { #[input] data: [String; 2] } },
+            quote! { struct Example { #[input] data: [&str; 2] } },
+            quote! { struct Example { #[input] data: u7 } },
+            quote! { struct Example { #[output] data: std::primitive::usize } },
+            quote! { struct Example { #[output] data: Option<u8> } },
+            quote! { struct Example { #[clock] #[input] clk: [bool; 2] } },
+        ] {
+            assert!(Module::from_attribute(quote! { ex_module }, item).is_err());
+        }
+
+        let item = quote! { struct Example { #[input] data: [u8; 4] } };
+        let module = Module::from_attribute(quote! { ex_module }, item).unwrap();
+        assert_eq!(Some(4), module.ports()[0].array_len());
+        assert_eq!(DataType::U8, module.ports()[0].data_type());
+    }
+
     #[test]
     fn module_from_attribute() -> err::Result<()> {
         let attr = quote! { ex_module };
@@ -396,25 +1930,38 @@ mod tests {
             module,
             Module {
                 name: "ex_module".into(),
-                clock: Some(("clk".into(), DataType::Bool)),
+                clocks: vec![Clock {
+                    name: "clk".into(),
+                    data_type: DataType::Bool,
+                    edge: Edge::Posedge,
+                    period_ps: None,
+                }],
+                reset: None,
+                options: Options::default(),
                 vis: String::from("pub"),
                 ident: syn::Ident::new("Example", Span::call_site()),
                 ports: vec![
                     Port {
                         name: "clk".into(),
                         data_type: DataType::Bool,
+                        array_len: None,
+                        rust_type: None,
                         input: true,
                         output: false,
                     },
                     Port {
                         name: "a".into(),
                         data_type: DataType::U8,
+                        array_len: None,
+                        rust_type: None,
                         input: true,
                         output: false,
                     },
                     Port {
                         name: "b".into(),
                         data_type: DataType::U64,
+                        array_len: None,
+                        rust_type: None,
                         input: false,
                         output: true,
                     }
@@ -425,7 +1972,23 @@ mod tests {
         Ok(())
     }
 
-    fn snapshot(name: &str, stream: TokenStream) {
+    #[test]
+    fn ferrilate_outputs() -> err::Result<()> {
+        let attr = quote! { ex_module };
+        let item = quote! {
+            pub struct Example {
+                #[output]
+                b: u8,
+            }
+        };
+
+        let output = ferrilate_attribute(attr, item)?;
+
+        snapshot("outputs.rs", output);
+        Ok(())
+    }
+
+    pub(crate) fn snapshot(name: &str, stream: TokenStream) {
         let mut path = std::path::PathBuf::from("snapshots");
         path.push(name);
 
@@ -435,7 +1998,7 @@ mod tests {
         if std::env::var("SNAP").is_ok() {
             use std::io::Write;
             let mut file = std::fs::File::create(path).unwrap();
-            file.write(source.as_bytes()).unwrap();
+            file.write_all(source.as_bytes()).unwrap();
         } else {
             use std::io::Read;
             let mut file = std::fs::File::open(path).unwrap();
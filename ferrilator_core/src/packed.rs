@@ -0,0 +1,206 @@
+use crate::as_tokens;
+use crate::err;
+use proc_macro2::Ident;
+use proc_macro2::Literal;
+use proc_macro2::Span;
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::Fields;
+use syn::ItemStruct;
+use syn::LitInt;
+use syn::Type;
+use syn::parse2;
+
+/// Implement `ferrilator::Packed` for a struct whose fields are all marked
+/// `#[bits(n)]`. The first field takes the most significant bits, as in a
+/// SystemVerilog packed struct.
+pub fn derive_packed(item: TokenStream) -> err::Result<TokenStream> {
+    let item: ItemStruct = parse2(item)?;
+    let ident = &item.ident;
+    if !item.generics.params.is_empty() {
+        return err::input!("packed struct {ident} cannot be generic");
+    }
+    let Fields::Named(named) = &item.fields else {
+        return err::input!("packed struct {ident} must have named fields");
+    };
+
+    let mut fields = vec![];
+    for field in &named.named {
+        let name = field.ident.clone().unwrap();
+        let Some(attr) = field.attrs.iter().find(|attr| attr.path().is_ident("bits")) else {
+            return err::input!("field {name} of {ident} needs #[bits(n)]");
+        };
+        let bits: u32 = attr.parse_args::<LitInt>()?.base10_parse()?;
+        let kind = FieldKind::from_type(&field.ty);
+        if bits == 0 {
+            return err::input!("field {name} of {ident} must have at least one bit");
+        }
+        if let Some(max) = kind.max_bits()
+            && bits > max
+        {
+            return err::input!(
+                "field {name} of {ident} has {bits} bits, more than its type {} holds",
+                as_tokens(&field.ty)
+            );
+        }
+        fields.push((name, bits, kind));
+    }
+    if fields.is_empty() {
+        return err::input!("packed struct {ident} needs at least one field");
+    }
+
+    let width: u32 = fields.iter().map(|(_, bits, _)| bits).sum();
+    if width > 128 {
+        return err::input!("packed struct {ident} has {width} bits, at most 128 are supported");
+    }
+
+    let mut pack_terms = vec![];
+    let mut unpack_fields = vec![];
+    let mut checks = vec![];
+    let mut shift = width;
+    for (name, bits, kind) in &fields {
+        shift -= bits;
+        let mask = Literal::u128_suffixed(if *bits == 128 {
+            u128::MAX
+        } else {
+            (1 << bits) - 1
+        });
+        let value = match kind {
+            FieldKind::Bool | FieldKind::Int(_) => quote! { self.#name as u128 & #mask },
+            FieldKind::Packed(_) => quote! { ::ferrilator::Packed::pack(&self.#name) & #mask },
+        };
+        let raw = if shift == 0 {
+            quote! { bits & #mask }
+        } else {
+            quote! { (bits >> #shift) & #mask }
+        };
+        pack_terms.push(if shift == 0 {
+            quote! { (#value) }
+        } else {
+            quote! { ((#value) << #shift) }
+        });
+        unpack_fields.push(match kind {
+            FieldKind::Bool => quote! { #name: (#raw) != 0, },
+            FieldKind::Int(ty) if ty.starts_with('i') => {
+                // sign extend from the top bit of the field
+                let ty = Ident::new(ty, Span::call_site());
+                let spare = 128 - bits;
+                quote! { #name: ((((#raw) << #spare) as i128) >> #spare) as #ty, }
+            }
+            FieldKind::Int(ty) => {
+                let ty = Ident::new(ty, Span::call_site());
+                quote! { #name: (#raw) as #ty, }
+            }
+            FieldKind::Packed(ty) => {
+                let message = format!("field {name} of {ident} must be as wide as its type");
+                checks.push(quote! {
+                    const _: () = assert!(<#ty as ::ferrilator::Packed>::WIDTH == #bits, #message);
+                });
+                quote! { #name: <#ty as ::ferrilator::Packed>::unpack(#raw), }
+            }
+        });
+    }
+
+    Ok(quote! {
+        impl ::ferrilator::Packed for #ident {
+            const WIDTH: u32 = #width;
+
+            fn pack(&self) -> u128 {
+                #(#pack_terms)|*
+            }
+
+            fn unpack(bits: u128) -> Self {
+                #ident {
+                    #(#unpack_fields)*
+                }
+            }
+        }
+
+        #(#checks)*
+    })
+}
+
+/// How a field is converted to and from its bits.
+enum FieldKind {
+    Bool,
+    /// A primitive integer, by name.
+    Int(&'static str),
+    /// Another type implementing `Packed`, such as a nested struct.
+    Packed(Box<Type>),
+}
+
+impl FieldKind {
+    fn from_type(ty: &Type) -> FieldKind {
+        let name = as_tokens(ty).to_string();
+        match name.as_str() {
+            "bool" => FieldKind::Bool,
+            "u8" => FieldKind::Int("u8"),
+            "u16" => FieldKind::Int("u16"),
+            "u32" => FieldKind::Int("u32"),
+            "u64" => FieldKind::Int("u64"),
+            "u128" => FieldKind::Int("u128"),
+            "i8" => FieldKind::Int("i8"),
+            "i16" => FieldKind::Int("i16"),
+            "i32" => FieldKind::Int("i32"),
+            "i64" => FieldKind::Int("i64"),
+            "i128" => FieldKind::Int("i128"),
+            _ => FieldKind::Packed(Box::new(ty.clone())),
+        }
+    }
+
+    /// The most bits the field can hold, if known before the code is built.
+    fn max_bits(&self) -> Option<u32> {
+        match self {
+            FieldKind::Bool => Some(1),
+            FieldKind::Int(ty) => Some(ty[1..].parse().unwrap()),
+            FieldKind::Packed(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::snapshot;
+
+    #[test]
+    fn packed_derive() -> err::Result<()> {
+        let item = quote! {
+            struct Command {
+                #[bits(4)]
+                op: u8,
+                #[bits(12)]
+                addr: u16,
+                #[bits(6)]
+                offset: i8,
+                #[bits(8)]
+                header: Header,
+                #[bits(1)]
+                valid: bool,
+            }
+        };
+
+        let output = derive_packed(item)?;
+
+        snapshot("packed.rs", output);
+        Ok(())
+    }
+
+    #[test]
+    fn packed_errors() {
+        let cases = [
+            quote! { struct S { a: u8 } },
+            quote! { struct S { #[bits(0)] a: u8 } },
+            quote! { struct S { #[bits(9)] a: u8 } },
+            quote! { struct S { #[bits(2)] a: bool } },
+            quote! { struct S { #[bits(x)] a: u8 } },
+            quote! { struct S { #[bits(100)] a: u128, #[bits(100)] b: u128 } },
+            quote! { struct S(#[bits(8)] u8); },
+            quote! { struct S {} },
+            quote! { struct S<T> { #[bits(8)] a: T } },
+        ];
+        for item in cases {
+            assert!(derive_packed(item).is_err());
+        }
+    }
+}
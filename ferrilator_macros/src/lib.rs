@@ -1,4 +1,7 @@
+use ferrilator_core::dpi::dpi_export_attribute;
 use ferrilator_core::ferrilate_attribute;
+use ferrilator_core::hw_enum::derive_hw_enum;
+use ferrilator_core::packed::derive_packed;
 use proc_macro::TokenStream;
 
 #[proc_macro_attribute]
@@ -11,3 +14,36 @@ pub fn ferrilate(attr: TokenStream, item: TokenStream) -> TokenStream {
         }
     }
 }
+
+#[proc_macro_attribute]
+/// Mark a function to implement an `import "DPI-C"` function of a design.
+pub fn dpi_export(attr: TokenStream, item: TokenStream) -> TokenStream {
+    match dpi_export_attribute(attr.into(), item.into()) {
+        Ok(tok) => tok.into(),
+        Err(e) => {
+            panic!("{e}");
+        }
+    }
+}
+
+#[proc_macro_derive(Packed, attributes(bits))]
+/// Implement `ferrilator::Packed` for a struct with `#[bits(n)]` fields.
+pub fn packed(item: TokenStream) -> TokenStream {
+    match derive_packed(item.into()) {
+        Ok(tok) => tok.into(),
+        Err(e) => {
+            panic!("{e}");
+        }
+    }
+}
+
+#[proc_macro_derive(HwEnum, attributes(bits))]
+/// Implement `ferrilator::PortType` for an enum with explicit encodings.
+pub fn hw_enum(item: TokenStream) -> TokenStream {
+    match derive_hw_enum(item.into()) {
+        Ok(tok) => tok.into(),
+        Err(e) => {
+            panic!("{e}");
+        }
+    }
+}
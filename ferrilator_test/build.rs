@@ -1,4 +1,25 @@
 fn main() {
     ferrilator::build("Counter", "src/counter.rs", &["src/hdl/counter.sv"]).unwrap();
+    ferrilator::build("DpiCalc", "src/dpi_calc.rs", &["src/hdl/dpi_calc.sv"]).unwrap();
+    ferrilator::build("DpiRam", "src/dpi_ram.rs", &["src/hdl/dpi_ram.sv"]).unwrap();
+    ferrilator::build("Lanes", "src/lanes.rs", &["src/hdl/lanes.sv"]).unwrap();
+    ferrilator::build(
+        "NegCounter",
+        "src/neg_counter.rs",
+        &["src/hdl/neg_counter.sv"],
+    )
+    .unwrap();
+    ferrilator::build("Packet", "src/packet.rs", &["src/hdl/packet.sv"]).unwrap();
+    ferrilator::build("Plusargs", "src/plusargs.rs", &["src/hdl/plusargs.sv"]).unwrap();
+    ferrilator::build(
+        "ResetCounter",
+        "src/reset_counter.rs",
+        &["src/hdl/reset_counter.sv"],
+    )
+    .unwrap();
+    ferrilator::build("Swizzle", "src/swizzle.rs", &["src/hdl/swizzle.sv"]).unwrap();
+    ferrilator::build("Traffic", "src/traffic.rs", &["src/hdl/traffic.sv"]).unwrap();
+    ferrilator::build("TwoClocks", "src/two_clocks.rs", &["src/hdl/two_clocks.sv"]).unwrap();
+    ferrilator::build("Uninit", "src/uninit.rs", &["src/hdl/uninit.sv"]).unwrap();
     ferrilator::build("Wide", "src/wide.rs", &["src/hdl/wide.sv"]).unwrap();
 }
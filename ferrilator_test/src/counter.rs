@@ -22,6 +22,30 @@ struct Counter {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ferrilator::Clocked;
+    use ferrilator::Dut;
+    use ferrilator::Value;
+    use ferrilator::vectors::Vectors;
+    use ferrilator::wave;
+    use ferrilator::wave::Waves;
+    use std::cell::RefCell;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    fn run_until<D: Clocked>(
+        dut: &mut D,
+        limit: usize,
+        pred: impl Fn(&D) -> bool,
+    ) -> Option<usize> {
+        for ticks in 1..=limit {
+            dut.tick();
+            if pred(dut) {
+                return Some(ticks);
+            }
+        }
+        None
+    }
 
     #[test]
     fn test_counter() {
@@ -29,15 +53,15 @@ mod tests {
         dut.set_enable(true);
 
         dut.tick();
-        assert_eq!(0, dut.get_value());
+        assert_eq!(1, dut.get_value());
         assert_eq!(false, dut.get_overflow());
 
         dut.tick();
-        assert_eq!(1, dut.get_value());
+        assert_eq!(2, dut.get_value());
         assert_eq!(false, dut.get_overflow());
 
         dut.tick();
-        assert_eq!(2, dut.get_value());
+        assert_eq!(3, dut.get_value());
         assert_eq!(false, dut.get_overflow());
 
         dut.set_reset(true);
@@ -70,4 +94,183 @@ mod tests {
         assert_eq!(1, dut.get_value());
         assert_eq!(false, dut.get_overflow());
     }
+
+    #[test]
+    fn test_counter_expect() {
+        let mut dut = Counter::new();
+        dut.set_enable(true);
+        for count in 0..5 {
+            ferrilator::expect_eq!(dut, dut.get_value(), count);
+            dut.tick();
+        }
+        assert_eq!(5, dut.cycle());
+    }
+
+    #[test]
+    fn test_counter_history() {
+        let mut dut = Counter::new();
+        assert!(dut.history().is_none());
+        dut.record_history(3);
+        dut.set_enable(true);
+        dut.tick_n(6);
+
+        let history = dut.history().unwrap();
+        let cycles: Vec<_> = history.samples().map(|sample| sample.cycle()).collect();
+        assert_eq!(vec![4, 5, 6], cycles);
+        assert_eq!(
+            vec![Value::from(4u8), Value::from(5u8), Value::from(6u8)],
+            history.values("value").unwrap()
+        );
+
+        let waves = Waves::from_history(history).select(&["value"]).unwrap();
+        assert_eq!("value  <0x4><0x5><0x6>", waves.to_string());
+    }
+
+    #[test]
+    fn test_counter_golden() {
+        let golden = wave::Trace::read("golden/counter.vcd").unwrap();
+        let golden = golden.at_posedges("clk").unwrap();
+
+        let mut dut = Counter::new();
+        dut.record_history(8);
+        dut.set_enable(true);
+        dut.tick_n(3);
+        dut.set_enable(false);
+        dut.tick_n(2);
+
+        let actual = Waves::from_history(dut.history().unwrap());
+        let comparison = wave::compare(&golden, &actual, &["enable", "value", "overflow"]).unwrap();
+        assert_eq!(5, comparison.compared());
+        comparison.assert_match();
+    }
+
+    #[test]
+    #[should_panic(expected = "at cycle 3")]
+    fn test_counter_expect_fails() {
+        let mut dut = Counter::new();
+        dut.set_enable(true);
+        dut.tick_n(3);
+        ferrilator::expect!(dut, dut.get_overflow(), "no overflow");
+    }
+
+    #[test]
+    fn test_counter_generic() {
+        let mut dut = Counter::new();
+        dut.set_enable(true);
+
+        assert_eq!(
+            Some(5),
+            run_until(&mut dut, 100, |dut| dut.get_value() == 5)
+        );
+        assert_eq!(50_000, dut.time());
+
+        dut.finish();
+    }
+
+    #[test]
+    fn test_counter_by_name() {
+        let mut dut = Counter::new();
+        let names: Vec<_> = dut.ports().iter().map(|port| port.name()).collect();
+        assert_eq!(vec!["clk", "reset", "enable", "value", "overflow"], names);
+
+        dut.set_port("enable", Value::from(true)).unwrap();
+        assert_eq!(Ok(Value::from(true)), dut.get_port("enable"));
+
+        for _ in 0..3 {
+            dut.tick();
+        }
+        assert_eq!(Ok(Value::from(3u8)), dut.get_port("value"));
+        assert_eq!(Ok(Value::from(false)), dut.get_port("overflow"));
+
+        assert!(dut.set_port("value", Value::from(1u8)).is_err());
+        assert!(dut.set_port("enable", Value::from(2u8)).is_err());
+        assert!(dut.get_port("missing").is_err());
+    }
+
+    #[test]
+    fn test_counter_vectors() {
+        for path in ["vectors/counter.csv", "vectors/counter.jsonl"] {
+            let mut dut = Counter::new();
+            Vectors::load(path).unwrap().check(&mut dut);
+        }
+    }
+
+    #[test]
+    fn test_counter_sim() {
+        let mut dut = Counter::new();
+        let seen = ferrilator::sim::run(&mut dut, |sim| async move {
+            let seen = Rc::new(RefCell::new(vec![]));
+            let monitor = sim.clone();
+            let log = seen.clone();
+            sim.spawn(async move {
+                loop {
+                    monitor.signal_change("value").await;
+                    log.borrow_mut().push(monitor.with(|dut| dut.get_value()));
+                }
+            });
+
+            sim.with(|dut| dut.set_enable(true));
+            sim.cycles(3).await;
+            sim.set("enable", false);
+            sim.cycles(2).await;
+            seen.take()
+        });
+        assert_eq!(vec![1, 2, 3], seen);
+        assert_eq!(45_000, dut.time());
+    }
+
+    #[test]
+    fn test_counter_on_change() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut dut = Counter::new();
+        let log = seen.clone();
+        dut.on_change_value(move |old, new| log.lock().unwrap().push((old, new)));
+        let wrapped = Arc::new(Mutex::new(0));
+        let count = wrapped.clone();
+        dut.on_change_overflow(move |_, new| {
+            if new {
+                *count.lock().unwrap() += 1;
+            }
+        });
+
+        dut.set_enable(true);
+        dut.tick_n(3);
+        dut.set_enable(false);
+        dut.tick_n(2);
+        assert_eq!(vec![(0, 1), (1, 2), (2, 3)], *seen.lock().unwrap());
+        assert_eq!(0, *wrapped.lock().unwrap());
+    }
+
+    #[test]
+    #[cfg(not(ferrilator_global_context))]
+    fn test_counter_threads() {
+        let handles: Vec<_> = (0..8)
+            .map(|n| {
+                std::thread::spawn(move || {
+                    let mut dut = Counter::new();
+                    dut.set_enable(true);
+                    dut.tick_n(10 * n);
+                    (n, dut.get_value(), dut.time())
+                })
+            })
+            .collect();
+        for handle in handles {
+            let (n, value, time) = handle.join().unwrap();
+            assert_eq!(10 * n as u8, value);
+            assert_eq!(100_000 * n as u64, time);
+        }
+
+        // a model can also be handed to another thread part way through
+        let mut dut = Counter::new();
+        dut.set_enable(true);
+        dut.tick_n(2);
+        let mut dut = std::thread::spawn(move || {
+            dut.tick_n(2);
+            dut
+        })
+        .join()
+        .unwrap();
+        dut.tick();
+        assert_eq!(5, dut.get_value());
+    }
 }
@@ -0,0 +1,80 @@
+use ferrilator::attr::dpi_export;
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(dpi_calc)]
+struct DpiCalc {
+    #[clock]
+    #[input]
+    clk: bool,
+
+    #[input]
+    a: u32,
+
+    #[input]
+    b: u32,
+
+    #[output]
+    product: u64,
+
+    #[output]
+    total: u32,
+
+    #[output]
+    mirrored: u128,
+
+    #[output]
+    odd: bool,
+
+    #[output]
+    swapped: u128,
+}
+
+#[dpi_export]
+fn ref_mul(a: u32, b: u32) -> u64 {
+    a as u64 * b as u64
+}
+
+#[dpi_export]
+fn ref_sum(values: &[u32], total: &mut u32) {
+    *total = values.iter().sum();
+}
+
+#[dpi_export]
+fn ref_mirror(#[bits(128)] value: u128, #[bits(128)] mirrored: &mut u128) -> bool {
+    *mirrored = value.reverse_bits();
+    value.count_ones() % 2 == 1
+}
+
+#[dpi_export]
+fn ref_swap(#[bits(96)] value: u128, #[bits(96)] swapped: &mut u128) {
+    // swap the 48 bit halves
+    *swapped = (value >> 48) | ((value & 0xffff_ffff_ffff) << 48);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dpi_imports() {
+        let mut dut = DpiCalc::new();
+        dut.set_a(100_000);
+        dut.set_b(300_000);
+        dut.tick();
+        assert_eq!(30_000_000_000, dut.get_product());
+        assert_eq!(400_003, dut.get_total());
+
+        let packed = (100_000u128 << 96) | (300_000u128 << 64) | 1;
+        assert_eq!(packed.reverse_bits(), dut.get_mirrored());
+        assert_eq!(true, dut.get_odd());
+        let packed = (100_000u128 << 64) | (300_000u128 << 32) | 1;
+        assert_eq!(
+            (packed >> 48) | ((packed & 0xffff_ffff_ffff) << 48),
+            dut.get_swapped()
+        );
+
+        dut.set_b(1);
+        dut.tick();
+        assert_eq!(false, dut.get_odd());
+    }
+}
@@ -0,0 +1,42 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(dpi_ram, dpi(dump(words = 128)))]
+struct DpiRam {
+    #[clock]
+    #[input]
+    clk: bool,
+
+    #[input]
+    addr: u8,
+
+    #[output]
+    data: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dpi_exports() {
+        let mut dut = DpiRam::new();
+        for index in 0..4 {
+            dut.preload(index, 0x100 + index);
+        }
+        assert_eq!(0x102, dut.peek(2));
+
+        dut.set_addr(3);
+        dut.eval();
+        assert_eq!(0x103, dut.get_data());
+
+        let mut words = 0;
+        let mut count = -1;
+        assert_eq!(false, dut.dump(&mut words, &mut count));
+        assert_eq!(0, count);
+        assert_eq!(0x0000_0103_0000_0102_0000_0101_0000_0100, words);
+
+        dut.tick_n(3);
+        assert_eq!(true, dut.dump(&mut words, &mut count));
+        assert!(count > 0);
+    }
+}
@@ -0,0 +1,36 @@
+module dpi_calc(
+  input clk,
+  input [31:0] a,
+  input [31:0] b,
+  output reg [63:0] product,
+  output reg [31:0] total,
+  output reg [127:0] mirrored,
+  output reg odd,
+  output reg [127:0] swapped
+);
+
+  import "DPI-C" function longint unsigned ref_mul(input int unsigned a, input int unsigned b);
+  import "DPI-C" function void ref_sum(input int unsigned values[], output int unsigned total);
+  import "DPI-C" function bit ref_mirror(input bit [127:0] value, output bit [127:0] mirrored);
+  import "DPI-C" function void ref_swap(input bit [95:0] value, output bit [95:0] swapped);
+
+  int unsigned values [4];
+  int unsigned sum;
+  bit [127:0] mirror;
+  bit [95:0] swap;
+
+  always @(posedge clk) begin
+    values[0] = a;
+    values[1] = b;
+    values[2] = 1;
+    values[3] = 2;
+    ref_sum(values, sum);
+    odd <= ref_mirror({a, b, 64'd1}, mirror);
+    product <= ref_mul(a, b);
+    total <= sum;
+    mirrored <= mirror;
+    ref_swap({a, b, 32'd1}, swap);
+    swapped <= {32'd0, swap};
+  end
+
+endmodule
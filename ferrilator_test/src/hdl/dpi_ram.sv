@@ -0,0 +1,35 @@
+module dpi_ram(
+  input clk,
+  input [3:0] addr,
+  output [31:0] data
+);
+
+  export "DPI-C" function preload;
+  export "DPI-C" function peek;
+  export "DPI-C" function dump;
+
+  bit [31:0] mem [16];
+  int reads;
+
+  assign data = mem[addr];
+
+  always @(posedge clk) begin
+    reads <= reads + 1;
+  end
+
+  function void preload(input int index, input int value);
+    mem[index[3:0]] = value;
+  endfunction
+
+  function int peek(input int index);
+    return mem[index[3:0]];
+  endfunction
+
+  // the first four words and whether the RAM was read since reset
+  function bit dump(output bit [127:0] words, output int count);
+    words = {mem[3], mem[2], mem[1], mem[0]};
+    count = reads;
+    return reads != 0;
+  endfunction
+
+endmodule
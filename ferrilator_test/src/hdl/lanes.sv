@@ -0,0 +1,22 @@
+module lanes(
+  input clk,
+  input [31:0] seed,
+  output [31:0] sum
+);
+
+  // independent lanes give the scheduler work to spread across threads
+  reg [31:0] lane [0:7];
+
+  genvar i;
+  generate
+    for (i = 0; i < 8; i = i + 1) begin : step
+      always @(posedge clk) begin
+        lane[i] <= (lane[i] ^ seed) * 32'd1103515245 + i;
+      end
+    end
+  endgenerate
+
+  assign sum = lane[0] + lane[1] + lane[2] + lane[3]
+             + lane[4] + lane[5] + lane[6] + lane[7];
+
+endmodule
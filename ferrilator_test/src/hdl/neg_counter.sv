@@ -0,0 +1,13 @@
+module neg_counter(
+  input clk,
+  input enable,
+  output reg [7:0] value
+);
+
+  always @(negedge clk) begin
+    if (enable) begin
+      value <= value + 1;
+    end
+  end
+
+endmodule
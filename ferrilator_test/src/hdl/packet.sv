@@ -0,0 +1,24 @@
+typedef struct packed {
+  logic [3:0] op;
+  logic [11:0] addr;
+  logic valid;
+} command_t;
+
+typedef struct packed {
+  logic [63:0] data;
+  logic [7:0] tag;
+} beat_t;
+
+module packet(
+  input command_t command,
+  input beat_t beat,
+  output command_t next,
+  output beat_t inverted,
+  output [11:0] addr
+);
+
+  assign next = '{op: command.op + 1, addr: command.addr + 1, valid: !command.valid};
+  assign inverted = '{data: ~beat.data, tag: beat.tag};
+  assign addr = command.addr;
+
+endmodule
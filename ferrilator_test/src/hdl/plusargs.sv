@@ -0,0 +1,13 @@
+module plusargs(
+  output reg [31:0] seed,
+  output reg verbose
+);
+
+  initial begin
+    if ($value$plusargs("seed=%d", seed) == 0) begin
+      seed = 0;
+    end
+    verbose = $test$plusargs("verbose") != 0;
+  end
+
+endmodule
@@ -0,0 +1,15 @@
+module reset_counter(
+  input clk,
+  input rst_n,
+  output reg [7:0] value
+);
+
+  always @(posedge clk) begin
+    if (!rst_n) begin
+      value <= 100;
+    end else begin
+      value <= value + 1;
+    end
+  end
+
+endmodule
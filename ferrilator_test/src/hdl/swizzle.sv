@@ -0,0 +1,19 @@
+module swizzle(
+  input [7:0] data [0:3],
+  input [127:0] keys [0:1],
+  output [7:0] reversed [0:3],
+  output [127:0] mixed [0:1],
+  output [9:0] sum
+);
+
+  assign reversed[0] = data[3];
+  assign reversed[1] = data[2];
+  assign reversed[2] = data[1];
+  assign reversed[3] = data[0];
+
+  assign mixed[0] = keys[0] ^ keys[1];
+  assign mixed[1] = ~keys[1];
+
+  assign sum = data[0] + data[1] + data[2] + data[3];
+
+endmodule
@@ -0,0 +1,36 @@
+typedef enum logic [1:0] {
+  RED = 2'd0,
+  GREEN = 2'd1,
+  YELLOW = 2'd2
+} light_t;
+
+typedef enum logic [2:0] {
+  NOP = 3'd0,
+  GO = 3'd1,
+  STOP = 3'd2,
+  OFF = 3'd5
+} command_t;
+
+module traffic(
+  input clk,
+  input command_t command,
+  input [1:0] raw,
+  output light_t light,
+  output light_t cast
+);
+
+  initial light = RED;
+
+  always @(posedge clk) begin
+    case (command)
+      GO: light <= GREEN;
+      STOP: light <= light == GREEN ? YELLOW : RED;
+      OFF: light <= light_t'(2'd3);
+      default: ;
+    endcase
+  end
+
+  // any pattern, including ones that are not a light
+  assign cast = light_t'(raw);
+
+endmodule
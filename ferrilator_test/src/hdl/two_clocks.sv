@@ -0,0 +1,16 @@
+module two_clocks(
+  input core_clk,
+  input bus_clk,
+  output reg [15:0] core_count,
+  output reg [15:0] bus_count
+);
+
+  always @(posedge core_clk) begin
+    core_count <= core_count + 1;
+  end
+
+  always @(posedge bus_clk) begin
+    bus_count <= bus_count + 1;
+  end
+
+endmodule
@@ -0,0 +1,15 @@
+module uninit(
+  input clk,
+  input load,
+  input [63:0] data,
+  output reg [63:0] value
+);
+
+  // deliberately has no reset
+  always @(posedge clk) begin
+    if (load) begin
+      value <= data;
+    end
+  end
+
+endmodule
@@ -0,0 +1,47 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(lanes, threads = 2)]
+struct Lanes {
+    #[clock]
+    #[input]
+    clk: bool,
+
+    #[input]
+    seed: u32,
+
+    #[output]
+    sum: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Config;
+
+    /// The same lanes stepped in software.
+    fn expected(seed: u32, cycles: usize) -> u32 {
+        let mut lanes = [0u32; 8];
+        for _ in 0..cycles {
+            for (i, lane) in lanes.iter_mut().enumerate() {
+                *lane = (*lane ^ seed)
+                    .wrapping_mul(1103515245)
+                    .wrapping_add(i as u32);
+            }
+        }
+        lanes.iter().fold(0, |sum, lane| sum.wrapping_add(*lane))
+    }
+
+    #[test]
+    fn test_lanes_threads() {
+        for mut dut in [
+            Lanes::new(),
+            Lanes::with_threads(2),
+            Lanes::with_threads(4),
+            Lanes::with_config(&Config::new().with_threads(1)),
+        ] {
+            dut.set_seed(0x1234_5678);
+            dut.tick_n(20);
+            assert_eq!(expected(0x1234_5678, 20), dut.get_sum());
+        }
+    }
+}
@@ -1,2 +1,13 @@
 mod counter;
+mod dpi_calc;
+mod dpi_ram;
+mod lanes;
+mod neg_counter;
+mod packet;
+mod plusargs;
+mod reset_counter;
+mod swizzle;
+mod traffic;
+mod two_clocks;
+mod uninit;
 mod wide;
@@ -0,0 +1,74 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(neg_counter, coverage)]
+struct NegCounter {
+    #[clock(negedge)]
+    #[input]
+    clk: bool,
+
+    #[input]
+    enable: bool,
+
+    #[output]
+    value: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Clocked;
+    use ferrilator::Dut;
+
+    #[test]
+    fn test_neg_counter() {
+        let mut dut = NegCounter::new();
+        dut.set_enable(true);
+
+        dut.tick();
+        assert_eq!(1, dut.get_value());
+
+        dut.negedge();
+        assert_eq!(2, dut.get_value());
+
+        dut.set_enable(false);
+        dut.posedge();
+        assert_eq!(2, dut.get_value());
+
+        dut.set_enable(true);
+        dut.tick_n(3);
+        assert_eq!(5, dut.get_value());
+        assert_eq!(50_000, dut.time());
+    }
+
+    #[test]
+    fn test_neg_counter_generic() {
+        fn count<D: Clocked>(dut: &mut D, n: usize) {
+            dut.tick_n(n);
+        }
+
+        let mut dut = NegCounter::new();
+        dut.set_enable(true);
+        count(&mut dut, 5);
+        assert_eq!(5, dut.get_value());
+    }
+
+    #[test]
+    fn test_neg_counter_coverage() {
+        let mut dut = NegCounter::new();
+        dut.set_enable(true);
+        dut.tick_n(5);
+
+        let merged = ferrilator::coverage::collect("test_neg_counter_coverage", |path| {
+            dut.write_coverage(path)
+        })
+        .unwrap();
+        let own = ferrilator::coverage::dir().join("test_neg_counter_coverage.dat");
+        let text = std::fs::read_to_string(own).unwrap();
+        assert!(text.contains("neg_counter.sv"));
+        assert!(
+            std::fs::read_to_string(merged)
+                .unwrap()
+                .contains("neg_counter.sv")
+        );
+    }
+}
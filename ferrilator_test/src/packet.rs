@@ -0,0 +1,118 @@
+use ferrilator::Packed;
+use ferrilator::attr::ferrilate;
+
+// only built by the tests
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Packed)]
+struct Command {
+    #[bits(4)]
+    op: u8,
+    #[bits(12)]
+    addr: u16,
+    #[bits(1)]
+    valid: bool,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, Packed)]
+struct Beat {
+    #[bits(64)]
+    data: u64,
+    #[bits(8)]
+    tag: u8,
+}
+
+#[ferrilate(packet)]
+struct Packet {
+    #[input]
+    command: Command,
+
+    #[input]
+    beat: Beat,
+
+    #[output]
+    next: Command,
+
+    #[output]
+    inverted: Beat,
+
+    #[output]
+    addr: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Dut;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Packed)]
+    struct Signed {
+        #[bits(3)]
+        small: i8,
+        #[bits(17)]
+        command: Command,
+        #[bits(4)]
+        nibble: u8,
+    }
+
+    #[test]
+    fn test_packed_layout() {
+        let command = Command {
+            op: 0xa,
+            addr: 0x123,
+            valid: true,
+        };
+        assert_eq!(17, Command::WIDTH);
+        assert_eq!((0xa << 13) | (0x123 << 1) | 1, command.pack());
+        assert_eq!(command, Command::unpack(command.pack()));
+        // bits above the width are ignored
+        assert_eq!(command, Command::unpack(command.pack() | (1 << 17)));
+
+        let signed = Signed {
+            small: -3,
+            command,
+            nibble: 0xf,
+        };
+        assert_eq!(24, Signed::WIDTH);
+        assert_eq!((0b101 << 21) | (command.pack() << 4) | 0xf, signed.pack());
+        assert_eq!(signed, Signed::unpack(signed.pack()));
+    }
+
+    #[test]
+    fn test_packed_ports() {
+        let mut dut = Packet::new();
+        dut.set_command(Command {
+            op: 3,
+            addr: 0xfff,
+            valid: true,
+        });
+        dut.set_beat(Beat {
+            data: 0x0123_4567_89ab_cdef,
+            tag: 0x5a,
+        });
+        dut.eval();
+
+        assert_eq!(0xfff, dut.get_addr());
+        assert_eq!(
+            Command {
+                op: 4,
+                addr: 0,
+                valid: false,
+            },
+            dut.get_next()
+        );
+        assert_eq!(
+            Beat {
+                data: !0x0123_4567_89ab_cdef,
+                tag: 0x5a,
+            },
+            dut.get_inverted()
+        );
+
+        let port = ferrilator::PortInfo::find(dut.ports(), "beat").unwrap();
+        assert_eq!(72, port.width());
+        let next = dut.get_port("next").unwrap();
+        assert_eq!(17, next.width());
+        assert_eq!(4 << 13, next.bits());
+    }
+}
@@ -0,0 +1,34 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(plusargs)]
+struct Plusargs {
+    #[output]
+    seed: u32,
+
+    #[output]
+    verbose: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Config;
+
+    #[test]
+    fn test_plusargs() {
+        let mut dut = Plusargs::with_args(&["+seed=5", "+verbose"]);
+        dut.eval();
+        assert_eq!(5, dut.get_seed());
+        assert_eq!(true, dut.get_verbose());
+
+        let mut dut = Plusargs::with_config(&Config::new().with_args(&["+seed=12"]));
+        dut.eval();
+        assert_eq!(12, dut.get_seed());
+        assert_eq!(false, dut.get_verbose());
+
+        // each model has its own arguments
+        let mut dut = Plusargs::with_args(&[]);
+        dut.eval();
+        assert_eq!(0, dut.get_seed());
+    }
+}
@@ -0,0 +1,62 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(reset_counter, savable)]
+struct ResetCounter {
+    #[clock]
+    #[input]
+    clk: bool,
+
+    #[reset(active_low, cycles = 4, auto)]
+    #[input]
+    rst_n: bool,
+
+    #[output]
+    value: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Dut;
+
+    #[test]
+    fn test_reset_counter() {
+        let mut dut = ResetCounter::new();
+        assert_eq!(100, dut.get_value());
+        assert_eq!(40_000, dut.time());
+
+        dut.tick_n(3);
+        assert_eq!(103, dut.get_value());
+
+        dut.reset();
+        assert_eq!(100, dut.get_value());
+        assert_eq!(110_000, dut.time());
+    }
+
+    #[test]
+    fn test_reset_counter_save() {
+        let mut dut = ResetCounter::new();
+        let booted = dut.snapshot().unwrap();
+
+        dut.tick_n(5);
+        assert_eq!(105, dut.get_value());
+        let path = std::env::temp_dir().join(format!("reset_counter-{}.save", std::process::id()));
+        dut.save(&path).unwrap();
+
+        dut.restore_snapshot(&booted).unwrap();
+        assert_eq!(100, dut.get_value());
+        assert_eq!(40_000, dut.time());
+        dut.tick();
+        assert_eq!(101, dut.get_value());
+
+        let mut other = ResetCounter::new();
+        other.restore(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(105, other.get_value());
+        assert_eq!(90_000, other.time());
+        other.tick();
+        assert_eq!(106, other.get_value());
+
+        assert!(other.restore("/nonexistent/reset_counter.save").is_err());
+    }
+}
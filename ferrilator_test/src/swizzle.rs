@@ -0,0 +1,95 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(swizzle)]
+struct Swizzle {
+    #[input]
+    data: [u8; 4],
+
+    #[input]
+    keys: [u128; 2],
+
+    #[output]
+    reversed: [u8; 4],
+
+    #[output]
+    mixed: [u128; 2],
+
+    #[output]
+    sum: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Dut;
+    use ferrilator::Value;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_array_ports() {
+        let mut dut = Swizzle::new();
+        dut.set_data([1, 2, 3, 4]);
+        dut.eval();
+        assert_eq!([4, 3, 2, 1], dut.get_reversed());
+        assert_eq!(10, dut.get_sum());
+
+        dut.set_data_at(1, 200);
+        dut.eval();
+        assert_eq!(200, dut.get_reversed_at(2));
+        assert_eq!(208, dut.get_sum());
+
+        let key = 0x0123_4567_89ab_cdef_0011_2233_4455_6677;
+        dut.set_keys([key, u128::MAX]);
+        dut.eval();
+        assert_eq!([!key, 0], dut.get_mixed());
+    }
+
+    #[test]
+    fn test_array_elements_by_name() {
+        let mut dut = Swizzle::new();
+        let names: Vec<_> = dut.ports().iter().map(|port| port.name()).collect();
+        assert_eq!(
+            vec![
+                "data[0]",
+                "data[1]",
+                "data[2]",
+                "data[3]",
+                "keys[0]",
+                "keys[1]",
+                "reversed[0]",
+                "reversed[1]",
+                "reversed[2]",
+                "reversed[3]",
+                "mixed[0]",
+                "mixed[1]",
+                "sum",
+            ],
+            names
+        );
+
+        dut.set_port("data[3]", Value::new(7, 8)).unwrap();
+        dut.eval();
+        assert_eq!(7, dut.get_port("reversed[0]").unwrap().bits());
+        assert!(dut.get_port("reversed[4]").is_err());
+    }
+
+    #[test]
+    fn test_array_on_change() {
+        let seen = Arc::new(Mutex::new(vec![]));
+        let mut dut = Swizzle::new();
+        let log = seen.clone();
+        dut.on_change_reversed(move |old, new| log.lock().unwrap().push((old, new)));
+
+        dut.set_data_at(0, 5);
+        dut.eval();
+        assert_eq!(vec![([0; 4], [0, 0, 0, 5])], *seen.lock().unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "index 4 out of range for data of length 4")]
+    fn test_array_index_checked() {
+        let mut dut = Swizzle::new();
+        dut.set_data_at(4, 1);
+    }
+}
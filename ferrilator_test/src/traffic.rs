@@ -0,0 +1,99 @@
+use ferrilator::HwEnum;
+use ferrilator::attr::ferrilate;
+
+// only built by the tests
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, HwEnum)]
+#[bits(2)]
+enum Light {
+    Red = 0,
+    Green = 1,
+    Yellow = 2,
+}
+
+#[cfg_attr(not(test), allow(dead_code))]
+#[derive(Clone, Copy, Debug, PartialEq, HwEnum)]
+enum Command {
+    Nop = 0,
+    Go = 1,
+    Stop = 2,
+    Off = 5,
+}
+
+#[ferrilate(traffic)]
+struct Traffic {
+    #[clock]
+    #[input]
+    clk: bool,
+
+    #[input]
+    command: Command,
+
+    #[input]
+    raw: u8,
+
+    #[output]
+    light: Light,
+
+    #[output]
+    cast: Light,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Dut;
+    use ferrilator::PortType;
+
+    #[test]
+    fn test_enum_encoding() {
+        assert_eq!(2, Light::WIDTH);
+        assert_eq!(3, Command::WIDTH);
+        assert_eq!(5, Command::Off.to_bits());
+        assert_eq!(Ok(Command::Stop), Command::from_bits(2));
+        assert_eq!(Err(3), Command::from_bits(3));
+        // bits above the width are ignored
+        assert_eq!(Ok(Light::Green), Light::from_bits(0b101));
+    }
+
+    #[test]
+    fn test_enum_ports() {
+        let mut dut = Traffic::new();
+        dut.eval();
+        assert_eq!(Ok(Light::Red), dut.get_light());
+
+        dut.set_command(Command::Go);
+        dut.tick();
+        assert_eq!(Ok(Light::Green), dut.get_light());
+
+        dut.set_command(Command::Stop);
+        dut.tick();
+        assert_eq!(Ok(Light::Yellow), dut.get_light());
+        dut.tick();
+        assert_eq!(Ok(Light::Red), dut.get_light());
+
+        dut.set_command(Command::Off);
+        dut.tick();
+        assert_eq!(Err(3), dut.get_light());
+
+        dut.set_command(Command::Nop);
+        for raw in 0..4 {
+            dut.set_raw(raw);
+            dut.eval();
+            assert_eq!(Light::from_bits(raw as u128), dut.get_cast());
+        }
+    }
+
+    #[test]
+    fn test_enum_ports_by_name() {
+        let mut dut = Traffic::new();
+        // patterns that are not a variant can still be driven by name
+        dut.set_port("command", ferrilator::Value::new(7, 3))
+            .unwrap();
+        assert_eq!(7, dut.get_port("command").unwrap().bits());
+        assert!(
+            dut.set_port("command", ferrilator::Value::new(8, 4))
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,45 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(two_clocks)]
+struct TwoClocks {
+    #[clock(period_ps = 10_000)]
+    #[input]
+    core_clk: bool,
+
+    #[clock(period_ps = 25_000)]
+    #[input]
+    bus_clk: bool,
+
+    #[output]
+    core_count: u16,
+
+    #[output]
+    bus_count: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ferrilator::Dut;
+
+    #[test]
+    fn test_two_clocks() {
+        let mut dut = TwoClocks::new();
+
+        // core_clk rises at 5ns, 15ns, ... 95ns and bus_clk at 12.5ns, 37.5ns,
+        // 62.5ns and 87.5ns
+        dut.run_for(100);
+        assert_eq!(10, dut.get_core_count());
+        assert_eq!(4, dut.get_bus_count());
+        assert_eq!(100_000, dut.time());
+
+        dut.step();
+        assert_eq!(11, dut.get_core_count());
+        assert_eq!(4, dut.get_bus_count());
+        assert_eq!(105_000, dut.time());
+
+        dut.run_for(1_000);
+        assert_eq!(111, dut.get_core_count());
+        assert_eq!(44, dut.get_bus_count());
+    }
+}
@@ -0,0 +1,65 @@
+use ferrilator::attr::ferrilate;
+
+#[ferrilate(uninit, x_assign = unique, x_initial = unique)]
+struct Uninit {
+    #[clock]
+    #[input]
+    clk: bool,
+
+    #[input]
+    load: bool,
+
+    #[input]
+    data: u64,
+
+    #[output]
+    value: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn initial(seed: u64) -> u64 {
+        let mut dut = Uninit::with_seed(seed);
+        dut.eval();
+        dut.get_value()
+    }
+
+    #[test]
+    fn test_uninit_seeds() {
+        // without a seed registers start at zero
+        let mut dut = Uninit::new();
+        dut.eval();
+        assert_eq!(0, dut.get_value());
+
+        // the same seed gives the same values, others differ
+        assert_eq!(initial(1), initial(1));
+        let values: Vec<_> = (1..=4).map(initial).collect();
+        assert!(values.iter().any(|value| *value != values[0]));
+
+        let mut dut = Uninit::with_seed(3);
+        dut.set_load(true);
+        dut.set_data(0x1234);
+        dut.tick();
+        assert_eq!(0x1234, dut.get_value());
+    }
+
+    #[test]
+    fn test_uninit_xcheck() {
+        // value holds X until the first load
+        let report = ferrilator::xcheck::run(1..=4, Uninit::with_seed, 4, |dut, cycle| {
+            dut.set_load(cycle == 1);
+            dut.set_data(0xabcd);
+            dut.tick();
+        })
+        .unwrap();
+        assert!(!report.is_clean());
+        assert!(report.differences().iter().all(|d| d.port() == "value"));
+        assert!(!report.logic::<64>("value", 0).unwrap().is_known());
+        assert_eq!(
+            Some(0xabcd),
+            report.logic::<64>("value", 3).unwrap().known()
+        );
+    }
+}